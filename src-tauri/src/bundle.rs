@@ -0,0 +1,543 @@
+use crate::{
+    build_filament_profile_detailed, build_final, cancel::Cancellations, collect_profile_files,
+    has_profile_extension, normalize, profile::FilamentProfile, profile_extensions,
+    profile_file_avoiding, profile_vendor, resolve_chain, resolve_chain_for, resolve_chain_from,
+    sanitize_filename, settings::Settings, stamp_user_profile, system_vendor, undo::UndoStack,
+    user_filament_profiles, ExportProgress, ResolveOptions, EXPORT_PROGRESS_EVENT,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, State};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// `bundle_type` OrcaSlicer writes for, and expects from, filament bundles.
+const FILAMENT_BUNDLE_TYPE: &str = "filament config bundle";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleReport {
+    /// Where the export ended up; differs from the requested path on fallback
+    pub path: String,
+    pub warnings: Vec<String>,
+}
+
+/// Writes a zip archive holding `files` (archive path → contents).
+fn write_zip(path: &Path, files: &[(String, String)]) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(name.as_str(), options)
+            .and_then(|_| Ok(zip.write_all(contents.as_bytes())?))
+            .map_err(|e| format!("write {} into {}: {}", name, path.display(), e))?;
+    }
+    zip.finish()
+        .map_err(|e| format!("write {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// `path`, or the first `<stem> (n).<ext>` beside it that no earlier file of
+/// the same export took, numbering clashes the way `profile_file_for` does
+/// on disk, e.g. for `A/B` and `A_B` which both sanitize to `A_B`. The
+/// result is added to `taken`.
+fn reserve(taken: &mut BTreeSet<PathBuf>, path: PathBuf) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    let mut candidate = path.clone();
+    let mut n = 1;
+    while taken.contains(&candidate) {
+        n += 1;
+        candidate = path.with_file_name(format!("{stem} ({n}).{ext}"));
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+/// Name a profile file goes by: its `name` field, else the file name without
+/// its extension.
+fn profile_name(path: &Path, profile: &Map<String, Value>) -> String {
+    match profile.get("name").and_then(Value::as_str) {
+        Some(name) => name.to_string(),
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Every profile in `source`, a folder (searched recursively) or a zip
+/// archive such as a `.orca_filament` bundle, as name → raw JSON. The bundle
+/// manifest and files that aren't JSON objects are skipped; the first file
+/// wins when two share a name.
+pub fn read_profiles(source: &Path) -> Result<BTreeMap<String, Map<String, Value>>, String> {
+    let extensions = profile_extensions();
+    let mut out = BTreeMap::new();
+    let mut add = |path: &Path, raw: &str| {
+        if path
+            .file_name()
+            .is_some_and(|n| n == "bundle_structure.json")
+        {
+            return;
+        }
+        if let Ok(Value::Object(profile)) = serde_json::from_str(raw) {
+            out.entry(profile_name(path, &profile)).or_insert(profile);
+        }
+    };
+
+    if source.is_dir() {
+        let mut files = Vec::new();
+        let mut failed = Vec::new();
+        collect_profile_files(source, 0, &extensions, &mut files, &mut failed);
+        if let Some(f) = failed.first() {
+            return Err(format!("read {}: {}", f.path, f.error));
+        }
+        for path in files {
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or_default();
+            crate::ensure_profile_size(&path, size)?;
+            let raw =
+                fs::read_to_string(&path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+            add(&path, &raw);
+        }
+        return Ok(out);
+    }
+
+    for (name, raw) in read_zip(source)? {
+        let path = PathBuf::from(name);
+        if has_profile_extension(&path, &extensions) {
+            add(&path, &raw);
+        }
+    }
+    Ok(out)
+}
+
+/// Every file in the zip at `path` as (archive path, contents), in archive
+/// order.
+fn read_zip(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("open {}: {}", path.display(), e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("read {}: {}", path.display(), e))?;
+    let mut out = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive
+            .by_index(i)
+            .map_err(|e| format!("read {}: {}", path.display(), e))?;
+        if !entry.is_file() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let inner = path.join(&name);
+        crate::ensure_profile_size(&inner, entry.size())?;
+        let mut raw = String::new();
+        // the declared size can lie; never inflate more than the limit
+        entry
+            .take(crate::max_profile_bytes() + 1)
+            .read_to_string(&mut raw)
+            .map_err(|e| format!("read {} in {}: {}", name, path.display(), e))?;
+        crate::ensure_profile_size(&inner, raw.len() as u64)?;
+        out.push((name, raw));
+    }
+    Ok(out)
+}
+
+/// Exports the resolved profile as a `.orca_filament` bundle, which
+/// OrcaSlicer imports on double-click.
+///
+/// This mirrors what OrcaSlicer's own "export filament bundle" writes: a zip
+/// holding the preset as `<vendor>/<name>.json` plus a `bundle_structure.json`
+/// manifest listing it under its vendor, i.e. the `system/<vendor>` folder the
+/// profile ultimately inherits from. A profile with no system ancestor has no
+/// vendor to be filed under; it is written as plain JSON next to
+/// `output_path` instead, with a warning.
+#[tauri::command]
+pub fn export_orca_filament(
+    start: String,
+    output_path: String,
+    undo: State<'_, UndoStack>,
+) -> Result<BundleReport, String> {
+    crate::ensure_writable()?;
+    println!("exporting filament bundle {}", &start);
+    crate::ensure_installed()?;
+    let chain = resolve_chain(&start, &ResolveOptions::default())?.chain;
+    let vendor = chain.iter().rev().find_map(|l| system_vendor(&l.path));
+    let report = build_filament_profile_detailed(start.clone(), None)?;
+    let mut warnings = report.warnings;
+    let Value::Object(mut profile) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    stamp_user_profile(&mut profile, &report.name);
+    let preset =
+        serde_json::to_string_pretty(&Value::Object(profile)).map_err(|e| e.to_string())?;
+
+    let Some(vendor) = vendor else {
+        let path = PathBuf::from(&output_path).with_extension("json");
+        warnings.push(format!(
+            "'{}' doesn't inherit from a vendor profile, which a .orca_filament bundle needs; wrote plain JSON to {} instead",
+            report.name,
+            path.display()
+        ));
+        undo.record_while(&format!("export {}", start), &[&path], || {
+            fs::write(&path, preset).map_err(|e| format!("write {}: {}", path.display(), e))
+        })?;
+        Settings::update(|s| s.push_recent(&start))?;
+        return Ok(BundleReport {
+            path: path.display().to_string(),
+            warnings,
+        });
+    };
+
+    let preset_path = format!("{}/{}.json", vendor, sanitize_filename(&report.name));
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let structure = json!({
+        "user_name": "",
+        "user_id": "",
+        "version": "",
+        "bundle_id": format!("offline_{}_{}", report.name, stamp),
+        "bundle_type": FILAMENT_BUNDLE_TYPE,
+        "filament_name": report.name,
+        "printer_vendor": [{ "vendor": vendor, "filament_path": [preset_path] }],
+    });
+    let structure = serde_json::to_string_pretty(&structure).map_err(|e| e.to_string())?;
+
+    let path = PathBuf::from(&output_path);
+    undo.record_while(&format!("export {}", start), &[&path], || {
+        write_zip(
+            &path,
+            &[
+                ("bundle_structure.json".to_string(), structure),
+                (preset_path, preset),
+            ],
+        )
+    })?;
+    Settings::update(|s| s.push_recent(&start))?;
+    Ok(BundleReport {
+        path: output_path,
+        warnings,
+    })
+}
+
+/// Resolves every user profile and writes them as one zip per vendor into
+/// `output_dir`, e.g. `Polymaker.zip` holding `<name>.json` for each of that
+/// vendor's profiles. The vendor is the resolved `filament_vendor`; profiles
+/// without one go into `Unknown.zip`. Names that sanitize alike are
+/// numbered, within a zip and across vendors, as `<name> (2)` and on. Emits
+/// the same `export-progress` events as `export_all_resolved`; profiles that
+/// fail to resolve are reported there with `ok: false` and left out. Returns
+/// the zips written.
+#[tauri::command]
+pub fn export_library_by_vendor(
+    output_dir: String,
+    operation_id: Option<String>,
+    app: AppHandle,
+    undo: State<'_, UndoStack>,
+    ops: State<'_, Cancellations>,
+) -> Result<Vec<String>, String> {
+    crate::ensure_writable()?;
+    println!("exporting library by vendor to {}", &output_dir);
+    crate::ensure_installed()?;
+    let op = ops.start(operation_id);
+    let opts = ResolveOptions::default();
+    let profiles = user_filament_profiles();
+    let total = profiles.len();
+    let mut by_vendor: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    let mut entries: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+    for (i, (name, path)) in profiles.into_iter().enumerate() {
+        op.check()?;
+        let built = resolve_chain_from(&path, &opts)
+            .map_err(String::from)
+            .and_then(|res| {
+                let profile = build_final(&res.chain, &name)?.to_value()?;
+                let json = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+                Ok((profile_vendor(&res.chain), json))
+            });
+        let ok = built.is_ok();
+        if let Ok((vendor, json)) = built {
+            let vendor = vendor.unwrap_or_else(|| "Unknown".into());
+            let entry = PathBuf::from(format!("{}.json", sanitize_filename(&name)));
+            let entry = reserve(entries.entry(vendor.clone()).or_default(), entry);
+            by_vendor
+                .entry(vendor)
+                .or_default()
+                .push((entry.to_string_lossy().into_owned(), json));
+        }
+        let _ = app.emit(
+            EXPORT_PROGRESS_EVENT,
+            ExportProgress {
+                done: i + 1,
+                total,
+                name,
+                ok,
+            },
+        );
+    }
+
+    let dir = PathBuf::from(&output_dir);
+    let mut taken = BTreeSet::new();
+    let zips: Vec<PathBuf> = by_vendor
+        .keys()
+        .map(|vendor| {
+            let zip = dir.join(format!("{}.zip", sanitize_filename(vendor)));
+            reserve(&mut taken, zip)
+        })
+        .collect();
+    let paths: Vec<&Path> = zips.iter().map(PathBuf::as_path).collect();
+    undo.record_while("export library by vendor", &paths, || {
+        fs::create_dir_all(&dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        for (zip, files) in zips.iter().zip(by_vendor.values()) {
+            write_zip(zip, files)?;
+        }
+        Ok(())
+    })?;
+    Ok(zips.iter().map(|z| z.display().to_string()).collect())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitExport {
+    /// Files created or rewritten because their contents changed
+    pub written: Vec<String>,
+    /// Files that already held exactly what would be written
+    pub unchanged: usize,
+    /// Profiles that didn't resolve and were left out
+    pub failed: Vec<String>,
+}
+
+/// Writes every user profile, resolved, to `output_dir` as
+/// `<vendor>/<type>/<name>.json` for keeping under version control: values
+/// normalized as OrcaSlicer spells them (see `normalize::normalize_profile`),
+/// keys sorted, two-space indent, LF line endings and a final newline
+/// whatever the platform. A file whose contents wouldn't change isn't
+/// touched, so re-running only shows real changes; files of profiles that
+/// have since been removed are left for the caller to delete. Names that
+/// sanitize to the same file are numbered, `<name> (2).json` and on.
+#[tauri::command]
+pub fn export_library_for_git(
+    output_dir: String,
+    undo: State<'_, UndoStack>,
+) -> Result<GitExport, String> {
+    crate::ensure_writable()?;
+    println!("exporting library for git to {}", &output_dir);
+    crate::ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let dir = PathBuf::from(&output_dir);
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    let mut taken = BTreeSet::new();
+    let mut failed = Vec::new();
+    for (name, path) in user_filament_profiles() {
+        let built = resolve_chain_from(&path, &opts)
+            .map_err(String::from)
+            .and_then(|res| Ok((build_final(&res.chain, &name)?.to_value()?, res)));
+        let Ok((Value::Object(profile), res)) = built else {
+            failed.push(name);
+            continue;
+        };
+        let vendor = profile_vendor(&res.chain).unwrap_or_else(|| "Unknown".into());
+        let kind = profile
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("filament")
+            .to_string();
+        let normalized = Value::Object(normalize::normalize_profile(&profile));
+        let mut json = serde_json::to_string_pretty(&normalized).map_err(|e| e.to_string())?;
+        json.push('\n');
+        let file = dir
+            .join(sanitize_filename(&vendor))
+            .join(sanitize_filename(&kind))
+            .join(format!("{}.json", sanitize_filename(&name)));
+        // profiles come in name order, so clashes are numbered the same way every run
+        let file = reserve(&mut taken, file);
+        files.push((file, json));
+    }
+
+    let total = files.len();
+    let changed: Vec<(PathBuf, String)> = files
+        .into_iter()
+        .filter(|(file, json)| fs::read(file).ok().as_deref() != Some(json.as_bytes()))
+        .collect();
+    let unchanged = total - changed.len();
+    let paths: Vec<&Path> = changed.iter().map(|(p, _)| p.as_path()).collect();
+    if !paths.is_empty() {
+        undo.record_while("export library for git", &paths, || {
+            for (file, json) in &changed {
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("create {}: {}", parent.display(), e))?;
+                }
+                fs::write(file, json.as_bytes())
+                    .map_err(|e| format!("write {}: {}", file.display(), e))?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(GitExport {
+        written: changed
+            .iter()
+            .map(|(p, _)| p.display().to_string())
+            .collect(),
+        unchanged,
+        failed,
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Names of the profiles written to the user folder
+    pub imported: Vec<String>,
+    pub skipped: Vec<SkippedImport>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedImport {
+    /// Path of the file inside the archive
+    pub file: String,
+    pub reason: String,
+}
+
+/// Archive paths a `bundle_structure.json` manifest lists as presets, or
+/// `None` when `raw` has no `printer_vendor` list to go by.
+fn manifest_paths(raw: &str) -> Option<BTreeSet<String>> {
+    let manifest: Value = serde_json::from_str(raw).ok()?;
+    let vendors = manifest.get("printer_vendor")?.as_array()?;
+    Some(
+        vendors
+            .iter()
+            .filter_map(|v| v.get("filament_path")?.as_array())
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Imports the profiles in a zip such as a `.orca_filament` bundle into the
+/// first user filament folder, the receiving end of `export_orca_filament`.
+///
+/// With a `bundle_structure.json` manifest only the presets it lists are
+/// considered; without one, every profile file in the archive. A file is
+/// skipped, with the reason, when it doesn't parse as a JSON object, when
+/// what it inherits from is neither installed nor in the archive, or when a
+/// user profile of that name already exists; nothing is overwritten.
+#[tauri::command]
+pub fn import_bundle(
+    archive_path: String,
+    undo: State<'_, UndoStack>,
+) -> Result<ImportReport, String> {
+    crate::ensure_writable()?;
+    println!("importing bundle {}", &archive_path);
+    crate::ensure_installed()?;
+    let extensions = profile_extensions();
+    let files = read_zip(Path::new(&archive_path))?;
+    let listed = files
+        .iter()
+        .find(|(name, _)| name == "bundle_structure.json")
+        .and_then(|(_, raw)| manifest_paths(raw));
+
+    let mut skipped = Vec::new();
+    let skip = |file: &str, reason: String| SkippedImport {
+        file: file.to_string(),
+        reason,
+    };
+    if let Some(listed) = &listed {
+        for missing in listed
+            .iter()
+            .filter(|l| !files.iter().any(|(n, _)| n == *l))
+        {
+            skipped.push(skip(
+                missing,
+                "listed in the manifest but not in the archive".into(),
+            ));
+        }
+    }
+
+    let mut candidates: Vec<(String, String, Map<String, Value>)> = Vec::new();
+    for (file, raw) in &files {
+        let path = Path::new(file);
+        let wanted = match &listed {
+            Some(listed) => listed.contains(file),
+            None => file != "bundle_structure.json" && has_profile_extension(path, &extensions),
+        };
+        if !wanted {
+            continue;
+        }
+        match serde_json::from_str(raw) {
+            Ok(Value::Object(profile)) => {
+                candidates.push((file.clone(), profile_name(path, &profile), profile))
+            }
+            Ok(_) => skipped.push(skip(file, "not a JSON object".into())),
+            Err(e) => skipped.push(skip(file, format!("doesn't parse: {}", e))),
+        }
+    }
+
+    let in_bundle: BTreeSet<String> = candidates.iter().map(|(_, n, _)| n.clone()).collect();
+    let existing = user_filament_profiles();
+    let dir = crate::user_filament_dirs()
+        .into_iter()
+        .next()
+        .ok_or("no user filament directory to write to")?;
+    let mut writes: Vec<(String, PathBuf, String)> = Vec::new();
+    for (file, name, profile) in candidates {
+        if existing.contains_key(&name) {
+            skipped.push(skip(
+                &file,
+                format!("a user profile named '{}' already exists", name),
+            ));
+            continue;
+        }
+        if writes.iter().any(|(n, ..)| *n == name) {
+            skipped.push(skip(
+                &file,
+                format!("another file in the bundle is also '{}'", name),
+            ));
+            continue;
+        }
+        let parent = profile.get("inherits").and_then(Value::as_str);
+        if let Some(parent) = parent.filter(|p| !p.is_empty() && !in_bundle.contains(*p)) {
+            let leaf = match FilamentProfile::try_from(Value::Object(profile.clone())) {
+                Ok(leaf) => leaf,
+                Err(e) => {
+                    skipped.push(skip(&file, format!("doesn't load: {}", e)));
+                    continue;
+                }
+            };
+            if let Err(e) = resolve_chain_for(leaf, &ResolveOptions::default()) {
+                skipped.push(skip(&file, format!("can't resolve '{}': {}", parent, e)));
+                continue;
+            }
+        }
+        let contents =
+            serde_json::to_string_pretty(&Value::Object(profile)).map_err(|e| e.to_string())?;
+        // names that sanitize alike, e.g. `PLA/Fast` and `PLA:Fast`, get numbered
+        let reserved: Vec<PathBuf> = writes.iter().map(|(_, p, _)| p.clone()).collect();
+        let path = profile_file_avoiding(&dir, &name, &reserved);
+        writes.push((name, path, contents));
+    }
+
+    if writes.is_empty() {
+        return Ok(ImportReport {
+            imported: Vec::new(),
+            skipped,
+        });
+    }
+    let paths: Vec<&Path> = writes.iter().map(|(_, p, _)| p.as_path()).collect();
+    let imported = undo.record_while(&format!("import {}", archive_path), &paths, || {
+        fs::create_dir_all(&dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        let mut imported = Vec::new();
+        for (name, path, contents) in &writes {
+            fs::write(path, contents).map_err(|e| format!("write {}: {}", path.display(), e))?;
+            imported.push(name.clone());
+        }
+        Ok(imported)
+    })?;
+    Ok(ImportReport { imported, skipped })
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tauri::State;
+
+/// What a cancelled command returns as its error.
+pub const CANCELLED: &str = "cancelled";
+
+/// Long-running commands currently accepting cancellation, by the
+/// `operation_id` the frontend passed in.
+///
+/// A command calls [`Cancellations::start`] and then [`Operation::check`]
+/// between units of work; [`cancel_operation`] flips the flag from another
+/// IPC call.
+#[derive(Default)]
+pub struct Cancellations {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// One registered operation; unregisters itself when dropped.
+pub struct Operation<'a> {
+    id: Option<String>,
+    flag: Arc<AtomicBool>,
+    registry: &'a Cancellations,
+}
+
+impl Cancellations {
+    /// Registers `id`, if any; without an id the operation can't be cancelled.
+    pub fn start(&self, id: Option<String>) -> Operation<'_> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &id {
+            let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+            running.insert(id.clone(), flag.clone());
+        }
+        Operation {
+            id,
+            flag,
+            registry: self,
+        }
+    }
+
+    fn cancel(&self, id: &str) -> bool {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        match running.get(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Operation<'_> {
+    /// `Err(CANCELLED)` once the operation was cancelled.
+    pub fn check(&self) -> Result<(), String> {
+        if self.flag.load(Ordering::Relaxed) {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for Operation<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            let mut running = self
+                .registry
+                .running
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            // a newer operation may have reused the id
+            if running.get(id).is_some_and(|f| Arc::ptr_eq(f, &self.flag)) {
+                running.remove(id);
+            }
+        }
+    }
+}
+
+/// Asks the operation registered as `id` to stop. Returns false when no such
+/// operation is running, e.g. because it already finished.
+#[tauri::command]
+pub fn cancel_operation(id: String, ops: State<'_, Cancellations>) -> bool {
+    ops.cancel(&id)
+}
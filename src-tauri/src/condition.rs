@@ -0,0 +1,433 @@
+use crate::{normalize, wildcard_match};
+use serde_json::{Map, Value};
+
+/// Evaluates an OrcaSlicer `compatible_printers_condition` against the
+/// resolved printer profile `vars`.
+///
+/// Supported is the subset vendor profiles actually use: `and`/`or`/`not`
+/// (also `&&`, `||`, `!`), parentheses, the comparisons `==`, `!=`, `<`,
+/// `>`, `<=` and `>=`, and `=~`/`!~` against a `/regex/` made of literal
+/// text, `.` and `.*`. Operands are variables, optionally indexed like
+/// `nozzle_diameter[0]`, numbers and quoted strings; a bare variable is read
+/// as a boolean. A list variable without an index means its first item.
+///
+/// Anything else, including a variable the printer doesn't define, is an
+/// `Err` describing the problem rather than a guess either way.
+pub fn evaluate(expr: &str, vars: &Map<String, Value>) -> Result<bool, String> {
+    let tokens = tokenize(expr)?;
+    let mut p = Parser {
+        tokens: &tokens,
+        pos: 0,
+        vars,
+    };
+    let result = p.or()?;
+    match p.tokens.get(p.pos) {
+        None => Ok(result),
+        Some(t) => Err(format!("unexpected {} in '{}'", t.describe(), expr)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A variable or keyword, with the `[n]` following it if any
+    Ident(String, Option<usize>),
+    Number(f64),
+    Str(String),
+    Regex(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(s, None) => format!("'{}'", s),
+            Token::Ident(s, Some(i)) => format!("'{}[{}]'", s, i),
+            Token::Number(n) => format!("'{}'", n),
+            Token::Str(s) => format!("\"{}\"", s),
+            Token::Regex(s) => format!("/{}/", s),
+            Token::Op(op) => format!("'{}'", op),
+            Token::Open => "'('".into(),
+            Token::Close => "')'".into(),
+        }
+    }
+}
+
+/// Longest first, so `<=` isn't read as `<` followed by `=`
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "=~", "!~", "&&", "||", "<", ">", "!",
+];
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == '"' || (c == '/' && matches!(tokens.last(), Some(Token::Op("=~" | "!~")))) {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&d| d == c)
+                .ok_or_else(|| format!("unterminated {} in '{}'", c, expr))?;
+            let text: String = chars[i + 1..i + 1 + end].iter().collect();
+            tokens.push(if c == '"' {
+                Token::Str(text)
+            } else {
+                Token::Regex(text)
+            });
+            i += end + 2;
+        } else if c == '[' {
+            let end = chars[i..]
+                .iter()
+                .position(|&d| d == ']')
+                .ok_or_else(|| format!("unterminated [ in '{}'", expr))?;
+            let index: String = chars[i + 1..i + end].iter().collect();
+            let index = index
+                .trim()
+                .parse()
+                .map_err(|_| format!("unsupported index [{}] in '{}'", index, expr))?;
+            match tokens.last_mut() {
+                Some(Token::Ident(_, slot @ None)) => *slot = Some(index),
+                _ => {
+                    return Err(format!(
+                        "[{}] doesn't follow a variable in '{}'",
+                        index, expr
+                    ))
+                }
+            }
+            i += end + 1;
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let len = chars[i + 1..]
+                .iter()
+                .take_while(|d| d.is_ascii_digit() || **d == '.')
+                .count()
+                + 1;
+            let text: String = chars[i..i + len].iter().collect();
+            let n = text
+                .parse()
+                .map_err(|_| format!("bad number '{}' in '{}'", text, expr))?;
+            tokens.push(Token::Number(n));
+            i += len;
+        } else if c.is_alphabetic() || c == '_' {
+            let len = chars[i..]
+                .iter()
+                .take_while(|d| d.is_alphanumeric() || **d == '_')
+                .count();
+            tokens.push(Token::Ident(chars[i..i + len].iter().collect(), None));
+            i += len;
+        } else if let Some(op) = OPERATORS
+            .iter()
+            .find(|op| chars[i..].iter().take(op.len()).copied().eq(op.chars()))
+        {
+            tokens.push(Token::Op(op));
+            i += op.len();
+        } else {
+            return Err(format!("unsupported character '{}' in '{}'", c, expr));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A comparison operand once looked up
+enum Operand {
+    Number(f64),
+    Text(String),
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    vars: &'a Map<String, Value>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let t = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("condition ends unexpectedly")?;
+        self.pos += 1;
+        Ok(t)
+    }
+
+    /// Consumes the next token when it is one of the keywords or operators
+    fn eat(&mut self, words: &[&str]) -> bool {
+        let hit = match self.peek() {
+            Some(Token::Ident(s, None)) => words.contains(&s.as_str()),
+            Some(Token::Op(op)) => words.contains(op),
+            _ => false,
+        };
+        if hit {
+            self.pos += 1;
+        }
+        hit
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut v = self.and()?;
+        while self.eat(&["or", "||"]) {
+            // both sides are evaluated so an unsupported right side is reported
+            let rhs = self.and()?;
+            v = v || rhs;
+        }
+        Ok(v)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut v = self.not()?;
+        while self.eat(&["and", "&&"]) {
+            let rhs = self.not()?;
+            v = v && rhs;
+        }
+        Ok(v)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        if self.eat(&["not", "!"]) {
+            return Ok(!self.not()?);
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let v = self.or()?;
+            return match self.next()? {
+                Token::Close => Ok(v),
+                t => Err(format!("expected ')', found {}", t.describe())),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<bool, String> {
+        let lhs = self.next()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) if !matches!(*op, "&&" | "||" | "!") => *op,
+            _ => return self.truthy(&lhs),
+        };
+        self.pos += 1;
+        let rhs = self.next()?;
+        if matches!(op, "=~" | "!~") {
+            let Token::Regex(pattern) = &rhs else {
+                return Err(format!("expected a /regex/ after {}", op));
+            };
+            let text = match self.operand(&lhs)? {
+                Operand::Text(s) => s,
+                Operand::Number(n) => n.to_string(),
+            };
+            let matched = wildcard_match(&regex_to_wildcard(pattern)?, &text);
+            return Ok(matched == (op == "=~"));
+        }
+
+        match (self.operand(&lhs)?, self.operand(&rhs)?) {
+            (Operand::Number(a), Operand::Number(b)) => Ok(match op {
+                "==" => a == b,
+                "!=" => a != b,
+                "<" => a < b,
+                ">" => a > b,
+                "<=" => a <= b,
+                _ => a >= b,
+            }),
+            (a, b) => {
+                let text = |o: Operand| match o {
+                    Operand::Text(s) => s,
+                    Operand::Number(n) => n.to_string(),
+                };
+                let (a, b) = (text(a), text(b));
+                match op {
+                    "==" => Ok(a == b),
+                    "!=" => Ok(a != b),
+                    _ => Err(format!("can't compare '{}' {} '{}' as numbers", a, op, b)),
+                }
+            }
+        }
+    }
+
+    fn truthy(&self, t: &Token) -> Result<bool, String> {
+        match t {
+            Token::Ident(s, None) if s == "true" => Ok(true),
+            Token::Ident(s, None) if s == "false" => Ok(false),
+            Token::Ident(..) => normalize::as_bool(&self.lookup(t)?)
+                .ok_or_else(|| format!("{} isn't a boolean", t.describe())),
+            t => Err(format!("expected a condition, found {}", t.describe())),
+        }
+    }
+
+    /// The value a variable token stands for
+    fn lookup(&self, t: &Token) -> Result<Value, String> {
+        let Token::Ident(name, index) = t else {
+            unreachable!("only identifiers are looked up");
+        };
+        let v = self
+            .vars
+            .get(name)
+            .ok_or_else(|| format!("the printer doesn't define '{}'", name))?;
+        match v {
+            Value::Array(items) => {
+                let i = index.unwrap_or(0);
+                items
+                    .get(i)
+                    .cloned()
+                    .ok_or_else(|| format!("'{}' has no item {}", name, i))
+            }
+            v => Ok(v.clone()),
+        }
+    }
+
+    fn operand(&self, t: &Token) -> Result<Operand, String> {
+        Ok(match t {
+            Token::Number(n) => Operand::Number(*n),
+            Token::Str(s) => Operand::Text(s.clone()),
+            Token::Ident(..) => match self.lookup(t)? {
+                Value::Number(n) => Operand::Number(n.as_f64().unwrap_or_default()),
+                v => match normalize::as_number(&v) {
+                    Some(n) => Operand::Number(n),
+                    None => Operand::Text(v.as_str().unwrap_or_default().to_string()),
+                },
+            },
+            t => return Err(format!("expected a value, found {}", t.describe())),
+        })
+    }
+}
+
+/// Translates the regex subset conditions use into a `wildcard_match`
+/// pattern: `.*` becomes `*`, `.` becomes `?` and `\x` is a literal `x`.
+/// Regexes are matched against the whole text, as OrcaSlicer does.
+fn regex_to_wildcard(pattern: &str) -> Result<String, String> {
+    let unsupported = || format!("unsupported regex /{}/", pattern);
+    let mut out = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push('*');
+            }
+            '.' => out.push('?'),
+            '\\' => match chars.next() {
+                Some(d) if d != '*' && d != '?' && !d.is_alphanumeric() => out.push(d),
+                _ => return Err(unsupported()),
+            },
+            '*' | '?' | '+' | '[' | ']' | '(' | ')' | '{' | '}' | '|' | '^' | '$' => {
+                return Err(unsupported())
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn evaluates_vendor_style_conditions() {
+        let Value::Object(vars) = json!({
+            "printer_model": "MK3S",
+            "printer_notes": "Don't remove\nPRINTER_VENDOR_PRUSA3D PRINTER_MODEL_MK3",
+            "nozzle_diameter": ["0.4"],
+            "single_extruder_multi_material": "0",
+        }) else {
+            unreachable!();
+        };
+        let eval = |e: &str| evaluate(e, &vars);
+
+        assert_eq!(eval("nozzle_diameter[0]==0.4"), Ok(true));
+        assert_eq!(
+            eval("nozzle_diameter[0]>0.3 and nozzle_diameter[0]<0.5"),
+            Ok(true)
+        );
+        assert_eq!(
+            eval("printer_model==\"MK3S\" || printer_model==\"MK4\""),
+            Ok(true)
+        );
+        assert_eq!(
+            eval("printer_notes=~/.*PRINTER_VENDOR_PRUSA3D.*/ and printer_notes!~/.*MINI.*/"),
+            Ok(true)
+        );
+        assert_eq!(eval("! (printer_notes=~/.*MODEL_MK3.*/)"), Ok(false));
+        assert_eq!(eval("not single_extruder_multi_material"), Ok(true));
+        assert!(eval("printer_notes=~/.*MK[34].*/").is_err());
+        assert!(eval("bed_shape[0]==0").is_err());
+        assert!(eval("printer_model==").is_err());
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_than_or() {
+        let vars = Map::new();
+        let eval = |e: &str| evaluate(e, &vars);
+
+        assert_eq!(eval("true or false and false"), Ok(true));
+        assert_eq!(eval("(true or false) and false"), Ok(false));
+        assert_eq!(eval("false and true or true"), Ok(true));
+        assert_eq!(eval("not false and false"), Ok(false));
+        assert_eq!(eval("!(false && false)"), Ok(true));
+        assert_eq!(eval("not not true"), Ok(true));
+        assert_eq!(eval("false || !false && true"), Ok(true));
+    }
+
+    #[test]
+    fn regexes_match_the_whole_text() {
+        let Value::Object(vars) = json!({"printer_model": "MK3S"}) else {
+            unreachable!();
+        };
+        let eval = |e: &str| evaluate(e, &vars);
+
+        assert_eq!(eval("printer_model=~/MK3./"), Ok(true));
+        assert_eq!(eval("printer_model=~/MK3/"), Ok(false));
+        assert_eq!(eval("printer_model=~/.*3.*/"), Ok(true));
+        assert_eq!(eval("printer_model!~/MK4.*/"), Ok(true));
+        assert_eq!(eval("printer_model=~/MK3\\./"), Ok(false));
+        assert!(eval("printer_model=~\"MK3S\"").is_err());
+        assert!(eval("printer_model=~/MK3+/").is_err());
+    }
+
+    #[test]
+    fn malformed_conditions_are_errors() {
+        let Value::Object(vars) = json!({"printer_model": "MK3S", "nozzle_diameter": ["0.4"]})
+        else {
+            unreachable!();
+        };
+        for e in [
+            "",
+            "(",
+            "(true",
+            "true)",
+            "true true",
+            "and",
+            "printer_model=~/MK3",
+            "printer_model==\"MK3S",
+            "nozzle_diameter[",
+            "nozzle_diameter[x]==0.4",
+            "nozzle_diameter[3]==0.4",
+            "[0]==1",
+            "printer_model<\"MK4\"",
+            "printer_model # 1",
+            "1..2==1",
+        ] {
+            assert!(evaluate(e, &vars).is_err(), "{:?} should not evaluate", e);
+        }
+
+        // every truncation of a valid condition fails cleanly rather than panicking
+        let full = "!(nozzle_diameter[0]>=0.4 && printer_model=~/MK.*/) || printer_model!=\"A\"";
+        for (end, _) in full.char_indices() {
+            let _ = evaluate(&full[..end], &vars);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// The values OrcaSlicer gives a filament setting nobody set, as its config
+/// code defines them. Only keys with a fixed default are listed; anything
+/// missing there has no known default and is always kept.
+const FILAMENT: &str = include_str!("../defaults/filament.json");
+
+/// The unit each filament setting's value is in, as OrcaSlicer labels it
+/// in its settings tabs. Unitless settings, or ones whose unit depends on the
+/// printer, aren't listed.
+const UNITS: &str = include_str!("../defaults/units.json");
+
+/// Plausible `[min, max]` per setting, by `filament_type`, for spotting
+/// typos; `*` holds the ranges for every material, which a material's own
+/// entry narrows key by key.
+const RANGES: &str = include_str!("../defaults/ranges.json");
+
+/// `filament_type` (or `*`) → setting → `(min, max)`
+pub type Ranges = BTreeMap<String, BTreeMap<String, (f64, f64)>>;
+
+/// Built-in defaults for profiles of type `kind`, if the exporter knows them.
+pub fn for_type(kind: &str) -> Option<Map<String, Value>> {
+    let table = match kind {
+        "filament" => FILAMENT,
+        _ => return None,
+    };
+    serde_json::from_str(table).ok()
+}
+
+/// Setting key → unit, for the keys that have one.
+pub fn units() -> BTreeMap<String, String> {
+    serde_json::from_str(UNITS).unwrap_or_default()
+}
+
+/// The range table: `ranges.json` in the app data directory when the user
+/// has put one there, replacing the bundled table as a whole, otherwise the
+/// bundled one.
+pub fn ranges() -> Result<Ranges, String> {
+    let custom = crate::settings::app_config_dir().join("ranges.json");
+    match std::fs::read_to_string(&custom) {
+        Ok(s) => serde_json::from_str(&s).map_err(|e| format!("parse {}: {}", custom.display(), e)),
+        Err(_) => serde_json::from_str(RANGES).map_err(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,167 @@
+use crate::normalize::same_value;
+use serde_json::{json, Map, Value};
+
+/// The part of `target` that differs from `base`: what a child profile would
+/// have to set when inheriting from `base` to end up as `target`.
+///
+/// Nested objects are diffed key by key, because `deep_merge` merges them.
+/// Arrays and scalars are compared whole, because a child's value replaces
+/// the parent's outright; boolean spellings count as equal (see
+/// [`same_value`]). Keys only present in `base` can't be expressed as
+/// overrides and are ignored.
+pub fn overrides(target: &Map<String, Value>, base: &Map<String, Value>) -> Map<String, Value> {
+    let mut out = Map::new();
+    for (k, v) in target {
+        match (v, base.get(k)) {
+            (Value::Object(t), Some(Value::Object(b))) => {
+                let nested = overrides(t, b);
+                if !nested.is_empty() {
+                    out.insert(k.clone(), Value::Object(nested));
+                }
+            }
+            (v, Some(b)) if same_value(k, v, b) => {}
+            (v, _) => {
+                out.insert(k.clone(), v.clone());
+            }
+        }
+    }
+    out
+}
+
+/// Key-level differences between two raw profiles, as
+/// `{"added": {key: new}, "removed": {key: old}, "changed": {key: {"from", "to"}}}`.
+///
+/// Nested objects are compared key by key and reported under dotted paths
+/// (`a.b`); arrays and scalars are compared whole, with boolean spellings
+/// counting as equal, as in [`overrides`].
+pub fn changes(old: &Map<String, Value>, new: &Map<String, Value>) -> Value {
+    let mut added = Map::new();
+    let mut removed = Map::new();
+    let mut changed = Map::new();
+    collect_changes("", old, new, &mut added, &mut removed, &mut changed);
+    serde_json::json!({ "added": added, "removed": removed, "changed": changed })
+}
+
+fn collect_changes(
+    prefix: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    added: &mut Map<String, Value>,
+    removed: &mut Map<String, Value>,
+    changed: &mut Map<String, Value>,
+) {
+    let path = |k: &str| format!("{prefix}{k}");
+    for (k, v) in new {
+        match (old.get(k), v) {
+            (None, v) => {
+                added.insert(path(k), v.clone());
+            }
+            (Some(Value::Object(o)), Value::Object(n)) => {
+                collect_changes(&format!("{}.", path(k)), o, n, added, removed, changed);
+            }
+            (Some(o), v) if !same_value(k, o, v) => {
+                changed.insert(path(k), serde_json::json!({ "from": o, "to": v }));
+            }
+            _ => {}
+        }
+    }
+    for (k, v) in old {
+        if !new.contains_key(k) {
+            removed.insert(path(k), v.clone());
+        }
+    }
+}
+
+/// An RFC 6902 JSON Patch that turns `base` into `target`: `add` for new
+/// keys, `replace` for changed ones and `remove` for keys `target` lacks.
+///
+/// Nested objects are patched key by key; arrays and scalars are replaced
+/// whole. Unlike [`overrides`], values are compared exactly, so applying the
+/// patch reproduces `target` byte for byte, boolean spellings included.
+pub fn json_patch(base: &Map<String, Value>, target: &Map<String, Value>) -> Vec<Value> {
+    let mut ops = Vec::new();
+    collect_patch("", base, target, &mut ops);
+    ops
+}
+
+/// A key escaped for use in a JSON Pointer (RFC 6901)
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn collect_patch(
+    prefix: &str,
+    base: &Map<String, Value>,
+    target: &Map<String, Value>,
+    ops: &mut Vec<Value>,
+) {
+    let path = |k: &str| format!("{}/{}", prefix, pointer_token(k));
+    for (k, v) in target {
+        match (base.get(k), v) {
+            (None, v) => ops.push(json!({ "op": "add", "path": path(k), "value": v })),
+            (Some(Value::Object(b)), Value::Object(t)) => collect_patch(&path(k), b, t, ops),
+            (Some(b), v) if b != v => {
+                ops.push(json!({ "op": "replace", "path": path(k), "value": v }))
+            }
+            _ => {}
+        }
+    }
+    for k in base.keys() {
+        if !target.contains_key(k) {
+            ops.push(json!({ "op": "remove", "path": path(k) }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies the subset of RFC 6902 `json_patch` emits
+    fn apply(doc: &mut Value, patch: &[Value]) {
+        for op in patch {
+            let path = op["path"].as_str().unwrap();
+            let (parent, key) = path.rsplit_once('/').unwrap();
+            let key = key.replace("~1", "/").replace("~0", "~");
+            let target = doc.pointer_mut(parent).unwrap().as_object_mut().unwrap();
+            match op["op"].as_str().unwrap() {
+                "add" | "replace" => {
+                    target.insert(key, op["value"].clone());
+                }
+                "remove" => {
+                    target.remove(&key).unwrap();
+                }
+                other => panic!("unexpected op {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn json_patch_round_trips() {
+        let base = json!({
+            "name": "Base",
+            "nozzle_temperature": ["200"],
+            "filament_soluble": ["0"],
+            "nested": { "a": "1", "b": "2", "gone": "x" },
+            "base_only": "y",
+            "a/b~c": "1",
+        });
+        let target = json!({
+            "name": "Child",
+            "nozzle_temperature": ["215", "220"],
+            "filament_soluble": ["false"],
+            "nested": { "a": "1", "b": "3", "c": { "deep": true } },
+            "a/b~c": "2",
+            "added": "z",
+        });
+        let (Value::Object(b), Value::Object(t)) = (&base, &target) else {
+            unreachable!();
+        };
+        let patch = json_patch(b, t);
+        let mut doc = base.clone();
+        apply(&mut doc, &patch);
+        assert_eq!(doc, target);
+        assert!(patch.contains(&json!({ "op": "replace", "path": "/a~1b~0c", "value": "2" })));
+        assert!(json_patch(t, t).is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    merge_chain,
+    normalize::same_value,
+    resolve_chain, sanitize_filename,
+    settings::{app_config_dir, Settings},
+    ResolveOptions, IDENTITY_KEYS,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::BTreeSet, fs, path::PathBuf};
+
+/// How a change in the base reaches the profile.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DriftKind {
+    /// The profile sets the key itself, so its value didn't move, but the
+    /// default it was overriding did.
+    Overridden,
+    /// The profile inherits the key, so its effective value moved with the base.
+    Inherited,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Drift {
+    pub key: String,
+    pub kind: DriftKind,
+    /// The base's value when the baseline was recorded
+    pub was: Option<Value>,
+    /// The base's value now
+    pub now: Option<Value>,
+    /// What the profile ends up with now
+    pub effective: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub name: String,
+    pub base: String,
+    /// No usable baseline existed, so one was recorded and nothing is reported
+    pub baseline_created: bool,
+    pub drift: Vec<Drift>,
+    /// Why nothing could be compared, e.g. no baseline in read-only mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// The resolved base as it looked last time it was accepted.
+#[derive(Serialize, Deserialize)]
+struct Baseline {
+    base: String,
+    values: Map<String, Value>,
+}
+
+fn baseline_path(name: &str) -> PathBuf {
+    app_config_dir()
+        .join("baselines")
+        .join(format!("{}.json", sanitize_filename(name)))
+}
+
+fn load_baseline(name: &str) -> Option<Baseline> {
+    let s = fs::read_to_string(baseline_path(name)).ok()?;
+    serde_json::from_str(&s).ok()
+}
+
+fn save_baseline(name: &str, baseline: &Baseline) -> Result<(), String> {
+    crate::ensure_writable()?;
+    let path = baseline_path(name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    }
+    let s = serde_json::to_string_pretty(baseline).map_err(|e| e.to_string())?;
+    fs::write(&path, s).map_err(|e| format!("write {}: {}", path.display(), e))
+}
+
+/// The profile's own keys and a fresh resolution of everything it inherits.
+fn resolve_split(name: &str) -> Result<(Map<String, Value>, Baseline), String> {
+    let chain = resolve_chain(name, &ResolveOptions::default())?.chain;
+    let Some((leaf, bases)) = chain.split_last() else {
+        return Err(format!("profile not found for '{}'", name));
+    };
+    let Some(base) = bases.last() else {
+        return Err(format!("'{}' doesn't inherit from another profile", name));
+    };
+    let (Value::Object(own), Value::Object(values)) =
+        (leaf.profile.to_value()?, merge_chain(bases).to_value()?)
+    else {
+        return Err(format!("'{}' did not resolve to an object", name));
+    };
+    Ok((
+        own,
+        Baseline {
+            base: base.name.clone(),
+            values,
+        },
+    ))
+}
+
+/// Keys whose value in the base changed since the baseline for `name` was
+/// recorded, leaving out identity keys and the stored ignored keys. The
+/// first check, or one after the profile was moved to another base, only
+/// records the baseline; in read-only mode it records nothing and says so
+/// in `note`.
+#[tauri::command]
+pub fn check_drift(name: String) -> Result<DriftReport, String> {
+    crate::ensure_installed()?;
+    let (own, current) = resolve_split(&name)?;
+    let baseline = match load_baseline(&name) {
+        Some(b) if b.base == current.base => b,
+        _ if crate::read_only() => {
+            return Ok(DriftReport {
+                name,
+                base: current.base,
+                baseline_created: false,
+                drift: Vec::new(),
+                note: Some(
+                    "read-only mode: no baseline to compare against, and none recorded".into(),
+                ),
+            });
+        }
+        _ => {
+            save_baseline(&name, &current)?;
+            return Ok(DriftReport {
+                name,
+                base: current.base,
+                baseline_created: true,
+                drift: Vec::new(),
+                note: None,
+            });
+        }
+    };
+
+    let ignored = Settings::load().ignored_keys();
+    let keys: BTreeSet<&String> = baseline
+        .values
+        .keys()
+        .chain(current.values.keys())
+        .filter(|k| !IDENTITY_KEYS.contains(&k.as_str()) && !ignored.contains(*k))
+        .collect();
+    let drift = keys
+        .into_iter()
+        .filter(
+            |k| match (baseline.values.get(*k), current.values.get(*k)) {
+                (Some(a), Some(b)) => !same_value(k, a, b),
+                (a, b) => a != b,
+            },
+        )
+        .map(|k| {
+            let (kind, effective) = match own.get(k) {
+                Some(v) => (DriftKind::Overridden, Some(v)),
+                None => (DriftKind::Inherited, current.values.get(k)),
+            };
+            Drift {
+                key: k.clone(),
+                kind,
+                was: baseline.values.get(k).cloned(),
+                now: current.values.get(k).cloned(),
+                effective: effective.cloned(),
+            }
+        })
+        .collect();
+    Ok(DriftReport {
+        name,
+        base: current.base,
+        baseline_created: false,
+        drift,
+        note: None,
+    })
+}
+
+/// Records the base as it resolves now, so `check_drift` stops reporting
+/// the changes seen so far.
+#[tauri::command]
+pub fn accept_drift(name: String) -> Result<(), String> {
+    crate::ensure_installed()?;
+    let (_, current) = resolve_split(&name)?;
+    save_baseline(&name, &current)
+}
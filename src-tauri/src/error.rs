@@ -0,0 +1,98 @@
+use crate::{slicer::Slicer, TypeCrossing};
+use std::{fmt, path::PathBuf};
+
+/// Failures callers may want to tell apart from ordinary IO/parse errors.
+///
+/// Commands keep returning `Result<_, String>`; `?` converts these through
+/// `Display`, so the frontend still receives a readable message.
+#[derive(Debug)]
+pub enum Error {
+    /// No data directory for the slicer at the expected location.
+    NotInstalled(Slicer, PathBuf),
+    /// No profile file answers to `name`.
+    NotFound {
+        name: String,
+        /// The profile whose `inherits` pointed at `name`, if any.
+        referenced_by: Option<String>,
+    },
+    /// A wildcard `inherits` matched more than one profile.
+    Ambiguous {
+        pattern: String,
+        matches: Vec<String>,
+    },
+    /// Following `inherits` came back around to this name.
+    Cycle(String),
+    /// A profile's `inherits` names the profile itself.
+    InheritsItself(String),
+    /// Building was asked to merge no layers at all, which would produce a
+    /// profile made of nothing but the stamped identity keys.
+    EmptyChain(String),
+    /// A profile inherits from one of another `type`; only an error under
+    /// `strict_types`, a warning otherwise.
+    TypeMismatch(TypeCrossing),
+    /// A profile file is bigger than the exporter reads; see
+    /// `max_profile_bytes`.
+    TooLarge {
+        path: PathBuf,
+        size: u64,
+        limit: u64,
+    },
+    /// A profile file couldn't be opened or read.
+    Io(String),
+    /// A profile file isn't valid JSON, or not shaped like a profile.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotInstalled(slicer, root) => {
+                write!(f, "{} not found at {}", slicer.label(), root.display())
+            }
+            Error::NotFound {
+                name,
+                referenced_by: Some(child),
+            } => write!(
+                f,
+                "profile '{}' (referenced by '{}') not found",
+                name, child
+            ),
+            Error::NotFound {
+                name,
+                referenced_by: None,
+            } => write!(f, "profile not found for '{}'", name),
+            Error::Ambiguous { pattern, matches } => write!(
+                f,
+                "'{}' matches several profiles: {}",
+                pattern,
+                matches.join(", ")
+            ),
+            Error::Cycle(name) => write!(f, "cycle detected at '{}'", name),
+            Error::InheritsItself(name) => write!(f, "profile '{}' inherits from itself", name),
+            Error::EmptyChain(name) => {
+                write!(f, "'{}' has no profiles in its inheritance chain", name)
+            }
+            Error::TypeMismatch(c) => write!(
+                f,
+                "'{}' is a {} profile but inherits from '{}', a {} profile",
+                c.child, c.child_type, c.parent, c.parent_type
+            ),
+            Error::TooLarge { path, size, limit } => write!(
+                f,
+                "{} is {} bytes, over the {} byte limit for profile files",
+                path.display(),
+                size,
+                limit
+            ),
+            Error::Io(msg) | Error::Parse(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for String {
+    fn from(e: Error) -> String {
+        e.to_string()
+    }
+}
@@ -0,0 +1,199 @@
+use crate::settings::app_config_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// File name → paths index of a slicer's `system` tree.
+///
+/// The index only depends on which files exist, so it remembers every
+/// directory it walked together with its mtime. Adding, removing or renaming
+/// an entry bumps the parent directory's mtime, which makes re-stating those
+/// directories enough to tell whether the index is still valid, without
+/// walking the tree again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SystemIndex {
+    root: PathBuf,
+    dirs: Vec<(PathBuf, SystemTime)>,
+    /// Keyed by file name, e.g. `Generic PLA.json`; paths in sorted order.
+    files: BTreeMap<String, Vec<PathBuf>>,
+}
+
+/// An index kept in memory. A partial one (from a walk that timed out)
+/// carries its warning and is served until `retry_after`, so a slow tree is
+/// rewalked every few seconds rather than on every lookup.
+struct Remembered {
+    index: Arc<SystemIndex>,
+    partial: Option<(String, Instant)>,
+}
+
+/// How long a timed-out index is used before the tree is walked again
+const PARTIAL_RETRY: Duration = Duration::from_secs(5);
+
+static MEMORY: Mutex<Option<HashMap<PathBuf, Remembered>>> = Mutex::new(None);
+
+fn remember(root: &Path, entry: Remembered) {
+    let mut memory = MEMORY.lock().unwrap_or_else(|e| e.into_inner());
+    memory
+        .get_or_insert_with(HashMap::new)
+        .insert(root.to_path_buf(), entry);
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+    let mut h = DefaultHasher::new();
+    root.hash(&mut h);
+    app_config_dir().join(format!("system-index-{:016x}.json", h.finish()))
+}
+
+impl SystemIndex {
+    /// Walks `root`; returns false alongside the index if `deadline` passed
+    /// before the walk finished.
+    fn build(root: &Path, deadline: Instant) -> (SystemIndex, bool) {
+        let mut index = SystemIndex {
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+        let complete = index.walk(root, deadline);
+        for paths in index.files.values_mut() {
+            paths.sort();
+        }
+        (index, complete)
+    }
+
+    fn walk(&mut self, dir: &Path, deadline: Instant) -> bool {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        let Some(modified) = mtime(dir) else {
+            return true;
+        };
+        self.dirs.push((dir.to_path_buf(), modified));
+        crate::stats::count_dir_scan();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return true;
+        };
+        let mut subdirs = Vec::new();
+        for e in entries.flatten() {
+            let p = e.path();
+            if p.is_dir() {
+                subdirs.push(p);
+            } else if let Some(name) = p.file_name() {
+                // keyed lossily so files with non-UTF-8 names still show up
+                let name = name.to_string_lossy().into_owned();
+                self.files.entry(name).or_default().push(p);
+            }
+        }
+        subdirs.sort();
+        subdirs.into_iter().all(|p| self.walk(&p, deadline))
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.dirs.iter().all(|(dir, t)| mtime(dir) == Some(*t))
+    }
+
+    fn load_cached(root: &Path) -> Option<SystemIndex> {
+        let s = fs::read_to_string(cache_path(root)).ok()?;
+        let index: SystemIndex = serde_json::from_str(&s).ok()?;
+        (index.root == root && index.is_fresh()).then_some(index)
+    }
+
+    fn save(&self) {
+        if crate::read_only() {
+            return;
+        }
+        let path = cache_path(&self.root);
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        // a missing cache only costs a rebuild, so failures are not errors
+        if let Ok(s) = serde_json::to_string(self) {
+            let _ = fs::write(path, s);
+        }
+    }
+
+    /// Every distinct file name in the tree, sorted.
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Every file in the tree.
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.values().flatten()
+    }
+
+    /// Every file called `file_name` in the tree, in sorted path order.
+    pub fn lookup(&self, file_name: &str) -> &[PathBuf] {
+        self.files.get(file_name).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// The index for `root`: from memory or the on-disk cache when still valid,
+/// otherwise rebuilt and cached. If the rebuild runs past `timeout`, the
+/// partial index is returned along with a warning; it is kept in memory for
+/// [`PARTIAL_RETRY`] but never written to disk.
+///
+/// The tree is walked without holding the memory lock, so a slow walk of one
+/// root doesn't stall lookups in another.
+pub fn system_index(root: &Path, timeout: Duration) -> (Arc<SystemIndex>, Option<String>) {
+    {
+        let memory = MEMORY.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = memory.as_ref().and_then(|m| m.get(root)) {
+            match &entry.partial {
+                Some((warning, retry_after)) if Instant::now() < *retry_after => {
+                    return (entry.index.clone(), Some(warning.clone()));
+                }
+                None if entry.index.is_fresh() => return (entry.index.clone(), None),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(index) = SystemIndex::load_cached(root) {
+        let index = Arc::new(index);
+        remember(
+            root,
+            Remembered {
+                index: index.clone(),
+                partial: None,
+            },
+        );
+        return (index, None);
+    }
+
+    let (index, complete) = SystemIndex::build(root, Instant::now() + timeout);
+    let index = Arc::new(index);
+    if !complete {
+        let warning = format!(
+            "indexing {} timed out after {} ms; results may be incomplete",
+            root.display(),
+            timeout.as_millis()
+        );
+        let partial = Some((warning.clone(), Instant::now() + PARTIAL_RETRY));
+        remember(
+            root,
+            Remembered {
+                index: index.clone(),
+                partial,
+            },
+        );
+        return (index, Some(warning));
+    }
+    index.save();
+    remember(
+        root,
+        Remembered {
+            index: index.clone(),
+            partial: None,
+        },
+    );
+    (index, None)
+}
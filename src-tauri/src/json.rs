@@ -0,0 +1,131 @@
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::{collections::HashSet, fmt};
+
+/// Paths of keys that appear more than once in the same object, in document
+/// order (`a.b` for nested objects, `a[0]` for array items).
+///
+/// `serde_json` silently keeps the last value for a repeated key, which is
+/// also what OrcaSlicer does; this only reports them.
+pub fn find_duplicate_keys(s: &str) -> Vec<String> {
+    let mut dupes = Vec::new();
+    let mut de = serde_json::Deserializer::from_str(s);
+    let scan = Scan {
+        path: String::new(),
+        dupes: &mut dupes,
+    };
+    // malformed input is reported by the real parse
+    let _ = scan.deserialize(&mut de);
+    dupes
+}
+
+struct Scan<'a> {
+    path: String,
+    dupes: &'a mut Vec<String>,
+}
+
+impl Scan<'_> {
+    fn child(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Scan<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<(), D::Error> {
+        de.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Scan<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut i = 0;
+        loop {
+            let item = Scan {
+                path: format!("{}[{}]", self.path, i),
+                dupes: &mut *self.dupes,
+            };
+            if seq.next_element_seed(item)?.is_none() {
+                return Ok(());
+            }
+            i += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = self.child(&key);
+            if !seen.insert(key) {
+                self.dupes.push(path.clone());
+            }
+            map.next_value_seed(Scan {
+                path,
+                dupes: &mut *self.dupes,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn reports_duplicated_keys_and_keeps_last_value() {
+        let s = r#"{
+            "nozzle_temperature": ["210"],
+            "nested": { "a": "1", "a": "2" },
+            "list": [{ "b": 1, "b": 2 }],
+            "nozzle_temperature": ["220"]
+        }"#;
+
+        assert_eq!(
+            find_duplicate_keys(s),
+            vec!["nested.a", "list[0].b", "nozzle_temperature"]
+        );
+        let v: Value = serde_json::from_str(s).unwrap();
+        assert_eq!(v["nozzle_temperature"][0], "220");
+    }
+
+    #[test]
+    fn no_duplicates() {
+        assert!(find_duplicate_keys(r#"{"a": {"a": 1}, "b": [1, 2]}"#).is_empty());
+    }
+}
@@ -1,20 +1,116 @@
-use serde_json::{json, Value};
+mod bundle;
+mod cancel;
+mod condition;
+mod defaults;
+mod diff;
+mod drift;
+mod error;
+mod index;
+mod json;
+mod library;
+mod normalize;
+mod profile;
+mod settings;
+mod slicer;
+mod snapshot;
+mod stats;
+mod trace;
+mod undo;
+mod validate;
+
+use cancel::Cancellations;
+use error::Error;
+use profile::FilamentProfile;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use settings::Settings;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ffi::OsStr,
     fs,
-    io::Read,
+    io::{Read, Write},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use tauri::{AppHandle, Emitter, State};
+use trace::{TraceMethod, TraceStep};
+use undo::UndoStack;
 
+/// Data directory of the active slicer (OrcaSlicer unless Bambu Studio was chosen)
 fn orca_root() -> PathBuf {
-    dirs_next::home_dir()
-        .unwrap_or_else(|| PathBuf::from("/"))
-        .join("Library/Application Support/OrcaSlicer")
+    slicer::active().root()
+}
+
+/// Set to anything but `0` or nothing to start the backend read-only: every
+/// command that would write, move or delete a file fails up front instead.
+const READ_ONLY_ENV: &str = "ORCA_EXPORTER_READ_ONLY";
+
+fn read_only() -> bool {
+    std::env::var_os(READ_ONLY_ENV).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Errors out in read-only mode; write commands call this before touching disk.
+fn ensure_writable() -> Result<(), String> {
+    ensure_writable_with(read_only())
+}
+
+/// [`ensure_writable`] with the mode passed in rather than read from the
+/// environment
+fn ensure_writable_with(read_only: bool) -> Result<(), String> {
+    if read_only {
+        Err(format!(
+            "read-only mode ({} is set): nothing can be written",
+            READ_ONLY_ENV
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Set to a number of bytes to change the largest profile file that is read
+const MAX_PROFILE_BYTES_ENV: &str = "ORCA_EXPORTER_MAX_PROFILE_BYTES";
+
+/// Used when `MAX_PROFILE_BYTES_ENV` isn't set; real profiles stay well
+/// under a megabyte even with long G-code blocks
+const DEFAULT_MAX_PROFILE_BYTES: u64 = 8 * 1024 * 1024;
+
+fn max_profile_bytes() -> u64 {
+    std::env::var(MAX_PROFILE_BYTES_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_PROFILE_BYTES)
+}
+
+/// Refuses a profile of `size` bytes when that's over the limit, so nothing
+/// that big is read into memory.
+fn ensure_profile_size(path: &Path, size: u64) -> Result<(), Error> {
+    let limit = max_profile_bytes();
+    if size > limit {
+        return Err(Error::TooLarge {
+            path: path.to_path_buf(),
+            size,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// The active slicer's data directory, or a clear error when it doesn't exist.
+fn ensure_installed() -> Result<PathBuf, Error> {
+    let slicer = slicer::active();
+    let root = slicer.root();
+    if root.is_dir() {
+        Ok(root)
+    } else {
+        Err(Error::NotInstalled(slicer, root))
+    }
 }
 
 fn user_filament_dirs() -> Vec<PathBuf> {
     let mut out = Vec::new();
     let user_root = orca_root().join("user");
+    stats::count_dir_scan();
     if let Ok(entries) = fs::read_dir(user_root) {
         for e in entries.flatten() {
             let p = e.path().join("filament");
@@ -23,196 +119,3781 @@ fn user_filament_dirs() -> Vec<PathBuf> {
             }
         }
     }
+    // read_dir order is platform dependent; keep lookups deterministic
+    out.sort();
     out
 }
 
-fn try_file(dir: &Path, name: &str) -> Option<PathBuf> {
-    println!("trying for file {} in path {:?}", &name, &dir);
-    let fname = if name.ends_with(".json") {
-        name.to_string()
+/// How many folders deep profiles are looked for under a user `filament` dir
+const MAX_USER_DEPTH: usize = 4;
+
+/// Every profile file under the user `filament` dirs, subfolders included.
+/// A folder's own files come before its subfolders', each in sorted order, so
+/// a top-level profile shadows a nested one with the same name. Folders that
+/// can't be read are skipped; see [`scan_user_profiles`].
+fn user_profile_files() -> Vec<PathBuf> {
+    scan_user_profiles().0
+}
+
+/// A folder that couldn't be listed while scanning for profiles
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirError {
+    path: String,
+    error: String,
+}
+
+/// Like [`user_profile_files`], but also returns the folders it couldn't read.
+fn scan_user_profiles() -> (Vec<PathBuf>, Vec<DirError>) {
+    let mut out = Vec::new();
+    let mut failed = Vec::new();
+    let user_root = orca_root().join("user");
+    stats::count_dir_scan();
+    if let Err(e) = fs::read_dir(&user_root) {
+        // no user folder just means no user profiles yet
+        if user_root.exists() {
+            failed.push(DirError {
+                path: user_root.display().to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
+    let extensions = profile_extensions();
+    for d in user_filament_dirs() {
+        collect_profile_files(&d, 0, &extensions, &mut out, &mut failed);
+    }
+    (out, failed)
+}
+
+/// Extensions profile files are recognized by; see
+/// `Settings::profile_extensions`
+fn profile_extensions() -> Vec<String> {
+    Settings::load().profile_extensions()
+}
+
+fn has_profile_extension(path: &Path, extensions: &[String]) -> bool {
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext))
+}
+
+/// `file_name` without the recognized extension it ends with, if any
+fn strip_profile_extension<'a>(file_name: &'a str, extensions: &[String]) -> Option<&'a str> {
+    let ext = Path::new(file_name)
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let e = extensions.iter().find(|e| e.eq_ignore_ascii_case(&ext))?;
+    Some(if e.is_empty() {
+        file_name
     } else {
-        format!("{name}.json")
+        &file_name[..file_name.len() - e.len() - 1]
+    })
+}
+
+/// File names a reference to `name` can be stored under, in the order the
+/// extensions are configured; a name already carrying one stands for itself.
+fn profile_file_names(name: &str, extensions: &[String]) -> Vec<String> {
+    let has_ext = Path::new(name).extension().is_some_and(|ext| {
+        extensions
+            .iter()
+            .any(|e| !e.is_empty() && e.eq_ignore_ascii_case(&ext.to_string_lossy()))
+    });
+    if has_ext {
+        return vec![name.to_string()];
+    }
+    extensions
+        .iter()
+        .map(|e| {
+            if e.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name}.{e}")
+            }
+        })
+        .collect()
+}
+
+fn collect_profile_files(
+    dir: &Path,
+    depth: usize,
+    extensions: &[String],
+    out: &mut Vec<PathBuf>,
+    failed: &mut Vec<DirError>,
+) {
+    stats::count_dir_scan();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            failed.push(DirError {
+                path: dir.display().to_string(),
+                error: e.to_string(),
+            });
+            return;
+        }
     };
-    let cand = dir.join(fname);
-    cand.is_file().then_some(cand)
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    let (subdirs, files): (Vec<PathBuf>, Vec<PathBuf>) =
+        paths.into_iter().partition(|p| p.is_dir());
+    out.extend(
+        files
+            .into_iter()
+            .filter(|p| p.is_file() && has_profile_extension(p, extensions)),
+    );
+    if depth < MAX_USER_DEPTH {
+        for d in subdirs {
+            collect_profile_files(&d, depth + 1, extensions, out, failed);
+        }
+    }
 }
 
-/// Recursively search under `dir` for `<name>.json`
-fn search_recursive(dir: &Path, name: &str) -> Option<PathBuf> {
-    let fname = if name.ends_with(".json") {
-        name.to_string()
-    } else {
-        format!("{name}.json")
+/// Whether the user profile file `path` is the one called `fname`.
+///
+/// A file whose name isn't valid UTF-8 can't be spelled in `inherits` or by
+/// the frontend, so it also answers to its lossy name (invalid bytes shown as
+/// U+FFFD) and to the `name` field inside it, which is what the listing shows.
+fn answers_to(path: &Path, fname: &str) -> bool {
+    let Some(file) = path.file_name() else {
+        return false;
     };
+    if file == OsStr::new(fname) {
+        return true;
+    }
+    if file.to_str().is_some() {
+        return false;
+    }
+    file.to_string_lossy() == fname
+        || load_profile(path, &mut Vec::new())
+            .ok()
+            .and_then(|p| p.name)
+            .is_some_and(|n| match path.extension() {
+                Some(ext) => format!("{n}.{}", ext.to_string_lossy()) == fname,
+                None => n == fname,
+            })
+}
+
+/// Options for resolving and building a profile; every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ResolveOptions {
+    /// How to pick between several profile files that answer to the same name.
+    /// In order of preference:
+    /// 1. user profiles, which shadow system ones unless the stored
+    ///    `PrecedencePolicy` puts system profiles first for that name;
+    /// 2. system profiles under `system/<vendor>`, when this is set;
+    /// 3. otherwise, under `prefer_child_vendor`, those under the folder
+    ///    named by the nearest descendant's `filament_vendor`;
+    /// 4. the first path in sorted order, also the tie-break within each group.
+    vendor: Option<String>,
+    /// Prefer the vendor tree the child's `filament_vendor` names when an
+    /// ancestor's name exists under several vendors; see `vendor`
+    prefer_child_vendor: bool,
+    /// Wall-clock budget for indexing the system tree, in milliseconds
+    search_timeout_ms: Option<u64>,
+    /// Only merge the layers above this ancestor and keep `inherits` pointing at it
+    flatten_to: Option<String>,
+    /// Fail the build when a required key resolves to an empty value
+    strict: bool,
+    /// Treat `*` and `?` in `inherits` as wildcards; see `expand_inherits`
+    wildcard_inherits: bool,
+    /// Type stamped into the result when no layer sets `type`: `filament`,
+    /// `process` or `machine`. Taken from the folder the profile lives in
+    /// when unset.
+    profile_type: Option<String>,
+    /// Leave `type` out when no layer sets it, instead of stamping a default
+    no_default_type: bool,
+    /// Fail when the chain crosses from one declared `type` to another,
+    /// rather than only warning; see `type_crossings`
+    strict_types: bool,
+    /// Leave out internal keys, those starting with one of `strip_prefixes`
+    strip_internal: bool,
+    /// Prefixes `strip_internal` removes; `INTERNAL_KEY_PREFIXES` when unset
+    strip_prefixes: Option<Vec<String>>,
+    /// Report the filesystem work the build did; see `stats::IoStats`
+    io_stats: bool,
+    /// Record every value each key took as the layers were merged; see
+    /// `merge_log`
+    merge_log: bool,
+    /// Fail the build when a layer gives a key a different JSON type than the
+    /// layer below it did, e.g. a number where the parent had a string; see
+    /// `value_type_changes`. Without it the child's value wins silently.
+    strict_merge: bool,
+    /// Rewrite numbers with a decimal comma (`"0,98"`) the way OrcaSlicer
+    /// writes them (`"0.98"`), warning about any whose comma could also
+    /// separate thousands; see `normalize::read_number`
+    canonical_numbers: bool,
+    /// Ancestor name → the file it must resolve to, skipping the search;
+    /// see `build_filament_profile_pinned`
+    pins: HashMap<String, String>,
+    /// Settings and user files shared by every lookup of a build, or of a
+    /// whole batch; loaded on first use when unset
+    #[serde(skip)]
+    lookup: Option<Arc<Lookup>>,
+}
+
+/// What name lookups read besides the system index, loaded once rather than
+/// per lookup.
+#[derive(Debug)]
+struct Lookup {
+    settings: Settings,
+    extensions: Vec<String>,
+    user_files: Vec<PathBuf>,
+}
 
-    // Fast check in current dir
-    let cand = dir.join(&fname);
-    if cand.is_file() {
-        return Some(cand);
+impl Lookup {
+    fn load() -> Arc<Lookup> {
+        let settings = Settings::load();
+        let extensions = settings.profile_extensions();
+        Arc::new(Lookup {
+            settings,
+            extensions,
+            user_files: user_profile_files(),
+        })
     }
+}
 
-    // Walk subdirectories
-    if let Ok(entries) = fs::read_dir(dir) {
-        for e in entries.flatten() {
-            let p = e.path();
-            if p.is_dir() {
-                if let Some(found) = search_recursive(&p, name) {
-                    return Some(found);
-                }
-            }
-        }
+/// Key prefixes that never name a real setting, only bookkeeping some tools
+/// add: a leading underscore and an `orca:` namespace.
+const INTERNAL_KEY_PREFIXES: &[&str] = &["_", "orca:"];
+
+/// Used when `search_timeout_ms` isn't set
+const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+impl ResolveOptions {
+    fn search_timeout(&self) -> Duration {
+        self.search_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT)
+    }
+
+    fn lookup(&self) -> Arc<Lookup> {
+        self.lookup.clone().unwrap_or_else(Lookup::load)
+    }
+
+    /// These options with the lookup state loaded, if it wasn't already
+    fn with_lookup(mut self) -> Self {
+        self.lookup.get_or_insert_with(Lookup::load);
+        self
     }
-    None
 }
 
-fn find_profile_file(name: &str) -> Option<PathBuf> {
-    // check user profiles first
-    for d in user_filament_dirs() {
-        if let Some(p) = try_file(&d, name) {
-            return Some(p);
+/// User and system candidates for one name, the preferred first.
+fn by_precedence(
+    user: Vec<PathBuf>,
+    system: Vec<PathBuf>,
+    precedence: settings::Precedence,
+) -> Vec<PathBuf> {
+    let (first, second) = match precedence {
+        settings::Precedence::UserFirst => (user, system),
+        settings::Precedence::SystemFirst => (system, user),
+    };
+    first.into_iter().chain(second).collect()
+}
+
+/// Vendor folder a system profile lives under, i.e. `system/<vendor>/...`
+fn system_vendor(path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(orca_root().join("system")).ok()?;
+    rel.components()
+        .next()
+        .and_then(|c| c.as_os_str().to_str())
+        .map(|s| s.to_string())
+}
+
+/// The file a profile name resolves to. When no user or system profile has
+/// the name at all, the stored alias table is consulted once: a renamed
+/// profile's old name maps to its new one, which is then looked up the same
+/// way, with a warning. Pins and relative paths in `inherits` are tried
+/// before any of this; see `walk_links`.
+fn find_profile_file(
+    name: &str,
+    vendor: Option<&str>,
+    opts: &ResolveOptions,
+    warnings: &mut Vec<String>,
+) -> Option<PathBuf> {
+    if let Some(found) = find_named_file(name, vendor, opts, warnings) {
+        return Some(found);
+    }
+    let alias = opts.lookup().settings.aliases.get(name)?.clone();
+    trace::record_name(name, TraceMethod::Alias, Some(&alias));
+    let found = find_named_file(&alias, vendor, opts, warnings)?;
+    warnings.push(format!(
+        "'{}' not found; using '{}', its alias",
+        name, alias
+    ));
+    Some(found)
+}
+
+fn find_named_file(
+    name: &str,
+    vendor: Option<&str>,
+    opts: &ResolveOptions,
+    warnings: &mut Vec<String>,
+) -> Option<PathBuf> {
+    let lookup = opts.lookup();
+    let fnames = profile_file_names(name, &lookup.extensions);
+
+    let user: Vec<PathBuf> = fnames
+        .iter()
+        .flat_map(|f| lookup.user_files.iter().filter(|p| answers_to(p, f)))
+        .cloned()
+        .collect();
+    for p in user
+        .iter()
+        .filter(|p| p.file_name().is_some_and(|f| f.to_str().is_none()))
+    {
+        warnings.push(format!(
+            "{} isn't valid UTF-8; matched '{}' by its converted name",
+            p.display(),
+            name
+        ));
+    }
+
+    let (index, warning) = index::system_index(&orca_root().join("system"), opts.search_timeout());
+    warnings.extend(warning);
+    let mut system: Vec<PathBuf> = fnames
+        .iter()
+        .flat_map(|f| index.lookup(f))
+        .cloned()
+        .collect();
+    if let Some(vendor) = vendor {
+        // stable sort keeps path order within each group
+        system.sort_by_key(|p| system_vendor(p).is_none_or(|v| !v.eq_ignore_ascii_case(vendor)));
+    }
+    let precedence = lookup.settings.precedence.for_name(name);
+    let candidates = by_precedence(user, system, precedence);
+
+    if candidates.len() > 1 {
+        let others: Vec<String> = candidates[1..]
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        warnings.push(format!(
+            "'{}' is ambiguous: using {} over {}",
+            name,
+            candidates[0].display(),
+            others.join(", ")
+        ));
+    }
+    trace::record(
+        name,
+        TraceMethod::Search,
+        candidates.first().map(PathBuf::as_path),
+        &candidates,
+    );
+    candidates.into_iter().next()
+}
+
+/// Shell-style match over the whole of `text`: `*` is any run of characters,
+/// possibly empty, `?` exactly one; everything else matches itself,
+/// case-sensitively.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut i, mut j) = (0, 0);
+    // last `*` seen and the text position it is currently stretched to
+    let mut star: Option<(usize, usize)> = None;
+    while j < t.len() {
+        if i < p.len() && (p[i] == '?' || p[i] == t[j]) {
+            i += 1;
+            j += 1;
+        } else if i < p.len() && p[i] == '*' {
+            star = Some((i, j));
+            i += 1;
+        } else if let Some((si, sj)) = star {
+            star = Some((si, sj + 1));
+            i = si + 1;
+            j = sj + 1;
+        } else {
+            return false;
         }
     }
+    p[i..].iter().all(|&c| c == '*')
+}
+
+/// Resolves a wildcard `inherits` to the one profile name it matches.
+///
+/// Candidates are the file names (without extension) of every user profile and
+/// of every file in the system index, the same names a plain `inherits` is
+/// looked up by. A name present in several places counts once; which file
+/// wins is then decided as for any other name. No match is `NotFound`, more
+/// than one is `Ambiguous` rather than a guess.
+fn expand_inherits(
+    pattern: &str,
+    referenced_by: Option<String>,
+    opts: &ResolveOptions,
+    warnings: &mut Vec<String>,
+) -> Result<String, Error> {
+    let (index, warning) = index::system_index(&orca_root().join("system"), opts.search_timeout());
+    warnings.extend(warning);
+    let lookup = opts.lookup();
+    let user_names = lookup
+        .user_files
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str());
+    let matches: BTreeSet<&str> = user_names
+        .chain(index.file_names())
+        .filter_map(|f| strip_profile_extension(f, &lookup.extensions))
+        .filter(|n| wildcard_match(pattern, n))
+        .collect();
+    let mut matches = matches.into_iter();
+    match (matches.next(), matches.next()) {
+        // the caller records the expansion in the trace
+        (Some(only), None) => Ok(only.to_string()),
+        (None, _) => Err(Error::NotFound {
+            name: pattern.to_string(),
+            referenced_by,
+        }),
+        (Some(first), Some(second)) => Err(Error::Ambiguous {
+            pattern: pattern.to_string(),
+            matches: [first, second]
+                .into_iter()
+                .chain(matches)
+                .map(str::to_string)
+                .collect(),
+        }),
+    }
+}
 
-    // search whole system tree
-    let sys_root = orca_root().join("system");
-    search_recursive(&sys_root, name)
+/// The file an `inherits` written as a path names, e.g. `base/PLA` or
+/// `../common.json`: relative to `dir`, the referring profile's folder, with
+/// either separator and an optional extension. `None` when that file doesn't
+/// exist or lies outside `root`, so `..` can't reach past the slicer's data.
+fn relative_inherit(dir: &Path, inherits: &str, root: &Path) -> Option<PathBuf> {
+    let rel = inherits.replace('\\', "/");
+    let root = root.canonicalize().ok()?;
+    profile_file_names(&rel, &profile_extensions())
+        .into_iter()
+        .filter_map(|rel| dir.join(rel).canonicalize().ok())
+        .find(|path| path.starts_with(&root) && path.is_file())
 }
 
-fn load_json(path: &Path) -> Result<Value, String> {
-    let mut f = fs::File::open(path).map_err(|e| format!("open {}: {}", path.display(), e))?;
+/// How often `load_json` tries a file that looks mid-write before giving up.
+const LOAD_ATTEMPTS: u32 = 3;
+/// Wait before the first retry; doubles after each further attempt.
+const LOAD_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Reads and parses `path` once. The flag says whether the failure looks
+/// transient, i.e. the file is locked or was cut short while OrcaSlicer is
+/// rewriting it, so reading again shortly may succeed.
+fn read_json(path: &Path) -> Result<(Value, String), (Error, bool)> {
+    let transient = |e: &std::io::Error| {
+        use std::io::ErrorKind::*;
+        matches!(e.kind(), PermissionDenied | Interrupted | WouldBlock)
+    };
+    stats::count_file_read();
+    let f = fs::File::open(path).map_err(|e| {
+        let t = transient(&e);
+        (Error::Io(format!("open {}: {}", path.display(), e)), t)
+    })?;
+    let size = f.metadata().map(|m| m.len()).unwrap_or_default();
+    ensure_profile_size(path, size).map_err(|e| (e, false))?;
     let mut s = String::new();
-    f.read_to_string(&mut s)
-        .map_err(|e| format!("read {}: {}", path.display(), e))?;
-    serde_json::from_str::<Value>(&s).map_err(|e| format!("parse {}: {}", path.display(), e))
+    // a file still growing past the limit is cut off and caught below
+    f.take(max_profile_bytes() + 1)
+        .read_to_string(&mut s)
+        .map_err(|e| {
+            let t = transient(&e);
+            (Error::Io(format!("read {}: {}", path.display(), e)), t)
+        })?;
+    ensure_profile_size(path, s.len() as u64).map_err(|e| (e, false))?;
+    match serde_json::from_str::<Value>(&s) {
+        Ok(v) => Ok((v, s)),
+        Err(e) => Err((
+            Error::Parse(format!("parse {}: {}", path.display(), e)),
+            e.is_eof(),
+        )),
+    }
 }
 
-fn deep_merge(into: &mut serde_json::Value, from: &serde_json::Value) {
-    if let (Some(a), Some(b)) = (into.as_object_mut(), from.as_object()) {
-        for (k, v) in b {
-            deep_merge(a.entry(k.clone()).or_insert(serde_json::Value::Null), v);
+/// Parses `path`; anything suspicious but not fatal is noted in `warnings`.
+/// A locked or truncated file is retried a few times with backoff, so a
+/// profile OrcaSlicer is saving right now doesn't fail the whole build.
+fn load_json(path: &Path, warnings: &mut Vec<String>) -> Result<Value, Error> {
+    let mut attempt = 1;
+    let (v, s) = loop {
+        match read_json(path) {
+            Ok(read) => break read,
+            Err((_, true)) if attempt < LOAD_ATTEMPTS => {
+                std::thread::sleep(LOAD_BACKOFF * 2u32.pow(attempt - 1));
+                attempt += 1;
+            }
+            Err((e, _)) => return Err(e),
         }
-    } else {
-        *into = from.clone();
+    };
+
+    let dupes = json::find_duplicate_keys(&s);
+    if !dupes.is_empty() {
+        warnings.push(format!(
+            "{} repeats {}; the last value of each wins",
+            path.display(),
+            dupes.join(", ")
+        ));
     }
+    Ok(v)
 }
 
-/// Returns bottom→top chain
-fn resolve_chain(start_name: &str) -> Result<Vec<(String, Value)>, String> {
-    println!("resolving chain for {}", &start_name);
+fn load_profile(path: &Path, warnings: &mut Vec<String>) -> Result<FilamentProfile, Error> {
+    FilamentProfile::try_from(load_json(path, warnings)?)
+        .map_err(|e| Error::Parse(format!("parse {}: {}", path.display(), e)))
+}
+
+/// One profile file along an inheritance chain.
+struct ChainLink {
+    name: String,
+    path: PathBuf,
+    /// When the file was last written, if the platform reports it
+    modified: Option<SystemTime>,
+    profile: FilamentProfile,
+}
+
+/// A resolved inheritance chain plus anything worth telling the user about it.
+struct Resolution {
+    /// bottom→top
+    chain: Vec<ChainLink>,
+    warnings: Vec<String>,
+}
+
+fn resolve_chain(start_name: &str, opts: &ResolveOptions) -> Result<Resolution, Error> {
+    walk_chain(start_name, None, opts)
+}
+
+/// Like [`resolve_chain`], but starts from a known leaf file instead of
+/// searching for it by name.
+fn resolve_chain_from(leaf: &Path, opts: &ResolveOptions) -> Result<Resolution, Error> {
+    let start_name = leaf
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    walk_chain(&start_name, Some(leaf.to_path_buf()), opts)
+}
+
+fn walk_chain(
+    start_name: &str,
+    leaf: Option<PathBuf>,
+    opts: &ResolveOptions,
+) -> Result<Resolution, Error> {
     let mut chain = Vec::new();
+    let mut warnings = Vec::new();
+    walk_links(start_name, leaf, opts, &mut chain, &mut warnings)?;
+    finish_chain(chain, warnings, opts)
+}
+
+/// Resolves the ancestors of a profile that only exists in memory, which
+/// becomes the leaf of the chain. It has no file, so its link's path is
+/// empty, and a path-style `inherits` can only be looked up by name.
+fn resolve_chain_for(leaf: FilamentProfile, opts: &ResolveOptions) -> Result<Resolution, Error> {
+    let name = leaf.name.clone().unwrap_or_default();
+    let inherits = leaf.inherits.clone();
+    let mut chain = vec![ChainLink {
+        name,
+        path: PathBuf::new(),
+        modified: None,
+        profile: leaf,
+    }];
+    let mut warnings = Vec::new();
+    if let Some(parent) = inherits {
+        walk_links(&parent, None, opts, &mut chain, &mut warnings)?;
+    }
+    finish_chain(chain, warnings, opts)
+}
+
+/// Turns a leaf-first walk into a [`Resolution`], checking its types
+fn finish_chain(
+    mut chain: Vec<ChainLink>,
+    mut warnings: Vec<String>,
+    opts: &ResolveOptions,
+) -> Result<Resolution, Error> {
+    chain.reverse();
+    let kept = chain.iter().rev().find_map(|l| l.profile.kind.clone());
+    for crossing in type_crossings(&chain) {
+        let e = Error::TypeMismatch(crossing);
+        if opts.strict_types {
+            return Err(e);
+        }
+        match &kept {
+            Some(kind) => warnings.push(format!("{}; the result keeps type '{}'", e, kind)),
+            None => warnings.push(e.to_string()),
+        }
+    }
+    Ok(Resolution { chain, warnings })
+}
+
+/// A profile inheriting, directly or through layers that declare no type,
+/// from one that declares a different type
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeCrossing {
+    pub child: String,
+    pub child_type: String,
+    pub parent: String,
+    pub parent_type: String,
+}
+
+/// Places in a bottom→top chain where a declared `type` disagrees with the
+/// one declared nearest below it, e.g. a filament inheriting from a machine
+/// profile. Layers without a `type` are looked through, so a conflict with
+/// untyped layers in between is still caught. The merged profile takes the
+/// topmost declared type, which is why any of these makes it ambiguous.
+fn type_crossings(chain: &[ChainLink]) -> Vec<TypeCrossing> {
+    let mut out = Vec::new();
+    let mut below: Option<(&ChainLink, &String)> = None;
+    for link in chain {
+        let Some(kind) = &link.profile.kind else {
+            continue;
+        };
+        if let Some((parent, parent_type)) = below {
+            if parent_type != kind {
+                out.push(TypeCrossing {
+                    child: link.name.clone(),
+                    child_type: kind.clone(),
+                    parent: parent.name.clone(),
+                    parent_type: parent_type.clone(),
+                });
+            }
+        }
+        below = Some((link, kind));
+    }
+    out
+}
+
+/// The walk behind [`walk_chain`]. Links are pushed leaf-first as they are
+/// found, so after an error `chain` holds everything up to the break.
+fn walk_links(
+    start_name: &str,
+    mut leaf: Option<PathBuf>,
+    opts: &ResolveOptions,
+    chain: &mut Vec<ChainLink>,
+    warnings: &mut Vec<String>,
+) -> Result<(), Error> {
+    println!("resolving chain for {}", &start_name);
     let mut seen = HashSet::new();
     let mut cursor = start_name.to_string();
     // println!("starting cursor {}", &cursor);
 
     loop {
         // println!("looping cursor {}", &cursor);
-        if !seen.insert(cursor.clone()) {
-            return Err(format!("cycle detected at '{}'", cursor));
-        }
-        let path = find_profile_file(&cursor)
-            .ok_or_else(|| format!("profile not found for '{}'", cursor))?;
-        let obj = load_json(&path)?;
-        let chain_name = obj
-            .get("name")
-            .and_then(Value::as_str)
-            .unwrap_or(&cursor)
-            .to_string();
-        chain.push((chain_name.clone(), obj.clone()));
-        if let Some(inh) = obj.get("inherits").and_then(Value::as_str) {
-            cursor = inh.to_string();
+        if opts.wildcard_inherits && leaf.is_none() && cursor.contains(['*', '?']) {
+            let referenced_by = chain.last().map(|l| l.name.clone());
+            let expanded = expand_inherits(&cursor, referenced_by, opts, warnings);
+            trace::record_name(&cursor, TraceMethod::Wildcard, expanded.as_deref().ok());
+            cursor = expanded?;
+        }
+        let pinned = match &leaf {
+            Some(path) => {
+                trace::record(&cursor, TraceMethod::Leaf, Some(path), &[]);
+                None
+            }
+            None => opts.pins.get(&cursor).map(PathBuf::from),
+        };
+        if let Some(path) = &pinned {
+            trace::record(&cursor, TraceMethod::Pin, Some(path), &[]);
+        }
+        let relative = match chain.last() {
+            Some(child) if leaf.is_none() && pinned.is_none() && cursor.contains(['/', '\\']) => {
+                let dir = child.path.parent().unwrap_or(Path::new(""));
+                let found = relative_inherit(dir, &cursor, &orca_root());
+                trace::record(&cursor, TraceMethod::Relative, found.as_deref(), &[]);
+                if found.is_none() {
+                    warnings.push(format!(
+                        "'{}' from '{}' isn't a file inside {}; looking it up by name",
+                        cursor,
+                        child.name,
+                        orca_root().display()
+                    ));
+                }
+                found
+            }
+            _ => None,
+        };
+        let path = match leaf.take().or(pinned).or(relative) {
+            Some(p) => p,
+            None => {
+                // a path that didn't resolve falls back to its file name
+                let name = cursor.rsplit(['/', '\\']).next().unwrap_or(&cursor);
+                let vendor = match &opts.vendor {
+                    Some(v) => Some(v.as_str()),
+                    // the nearest child that declares one, chain being leaf-first
+                    None if opts.prefer_child_vendor => chain
+                        .iter()
+                        .rev()
+                        .find_map(|l| l.profile.filament_vendor.as_ref()?.first())
+                        .map(String::as_str),
+                    None => None,
+                };
+                match find_profile_file(name, vendor, opts, warnings) {
+                    Some(p) => p,
+                    // nothing else answers to the name the child gave itself
+                    None => match chain.last() {
+                        Some(child) if is_own_name(child, name) => {
+                            return Err(Error::InheritsItself(child.name.clone()));
+                        }
+                        _ => {
+                            return Err(Error::NotFound {
+                                name: cursor.clone(),
+                                // chain is still leaf-first here, so the last link points at cursor
+                                referenced_by: chain.last().map(|l| l.name.clone()),
+                            });
+                        }
+                    },
+                }
+            }
+        };
+        // compared by file, so a user profile may inherit the system one of
+        // the same name when system profiles take precedence for it
+        if let Some(child) = chain.last().filter(|child| child.path == path) {
+            return Err(Error::InheritsItself(child.name.clone()));
+        }
+        if !seen.insert(path.clone()) {
+            return Err(Error::Cycle(cursor));
+        }
+        let profile = load_profile(&path, warnings)?;
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let chain_name = profile.name.clone().unwrap_or_else(|| cursor.clone());
+        let inherits = profile.inherits.clone();
+        chain.push(ChainLink {
+            name: chain_name,
+            path,
+            modified,
+            profile,
+        });
+        if let Some(inh) = inherits {
+            cursor = inh;
             println!("found achestor {}", &cursor);
         } else {
             break;
         }
     }
 
-    chain.reverse();
-    Ok(chain)
+    Ok(())
+}
+
+/// Whether `inherits` names `link` itself, by its `name` or its file stem
+fn is_own_name(link: &ChainLink, inherits: &str) -> bool {
+    let target = inherits.strip_suffix(".json").unwrap_or(inherits);
+    link.name == target
+        || link
+            .path
+            .file_stem()
+            .is_some_and(|s| s == OsStr::new(target))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainNode {
+    name: String,
+    path: String,
+    /// Last modification as a unix timestamp in seconds
+    modified: Option<u64>,
+}
+
+/// Resolves a profile object the caller holds, e.g. one posted over RPC,
+/// against the installed bases, as if it were a user profile file. The
+/// object must be named; nothing is written.
+#[tauri::command]
+fn resolve_value(leaf: Value) -> Result<Value, String> {
+    ensure_installed()?;
+    let leaf = FilamentProfile::try_from(leaf)?;
+    let Some(name) = leaf.name.clone() else {
+        return Err("profile object has no 'name'".into());
+    };
+    let res = resolve_chain_for(leaf, &ResolveOptions::default())?;
+    build_final(&res.chain, &name)?.to_value()
+}
+
+/// The files `name` inherits from, bottom→top, ending with `name` itself
+#[tauri::command]
+fn get_inheritance_chain(name: String) -> Result<Vec<ChainNode>, String> {
+    ensure_installed()?;
+    let res = resolve_chain(&name, &ResolveOptions::default())?;
+    Ok(res.chain.iter().map(chain_node).collect())
 }
 
-fn build_final(chain: &[(String, Value)], final_name: &str) -> Value {
-    let mut acc = json!({});
-    for (_, obj) in chain {
-        deep_merge(&mut acc, obj);
+fn chain_node(l: &ChainLink) -> ChainNode {
+    ChainNode {
+        name: l.name.clone(),
+        path: l.path.display().to_string(),
+        modified: l
+            .modified
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
     }
-    if let Value::Object(ref mut map) = acc {
-        map.remove("inherits");
-        map.insert("name".into(), Value::String(final_name.to_string()));
-        let from = chain
-            .last()
-            .and_then(|(_, o)| o.get("from").and_then(Value::as_str))
-            .unwrap_or("User");
-        map.insert("from".into(), Value::String(from.to_string()));
-        map.insert("instantiation".into(), Value::String("true".into()));
-        if !map.contains_key("type") {
-            map.insert("type".into(), Value::String("filament".into()));
-        }
+}
+
+/// Everything the editor shows for a profile in one call: `raw`, its file
+/// as written; `resolved`, the built profile; `chain`, as from
+/// `get_inheritance_chain`; and `warnings`. When the chain doesn't resolve,
+/// `resolved` is null, `error` says why and `chain` holds the layers found
+/// before the break; only a profile that can't be found or read at all
+/// fails outright.
+#[tauri::command]
+fn get_profile_full(name: String) -> Result<Value, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let mut warnings = Vec::new();
+    let path = find_profile_file(&name, opts.vendor.as_deref(), &opts, &mut warnings)
+        .ok_or_else(|| format!("profile not found for '{}'", name))?;
+    let raw = load_json(&path, &mut warnings)?;
+
+    let mut chain = Vec::new();
+    if let Err(e) = walk_links(&name, Some(path), &opts, &mut chain, &mut warnings) {
+        return Ok(serde_json::json!({
+            "raw": raw,
+            "resolved": null,
+            // walked leaf-first; shown bottom→top like a whole chain
+            "chain": chain.iter().rev().map(chain_node).collect::<Vec<_>>(),
+            "warnings": warnings,
+            "error": e.to_string(),
+        }));
+    }
+    let nodes: Vec<ChainNode> = chain.iter().rev().map(chain_node).collect();
+    // warnings from the walk are reported even when finishing the chain fails
+    let built = finish_chain(chain, warnings.clone(), &opts).and_then(|res| {
+        warnings = res.warnings;
+        let leaf = res.chain.last().map(|l| l.name.clone()).unwrap_or_default();
+        build_final(&res.chain, &leaf)
+    });
+    Ok(match built {
+        Ok(resolved) => serde_json::json!({
+            "raw": raw,
+            "resolved": resolved.to_value()?,
+            "chain": nodes,
+            "warnings": warnings,
+        }),
+        Err(e) => serde_json::json!({
+            "raw": raw,
+            "resolved": null,
+            "chain": nodes,
+            "warnings": warnings,
+            "error": e.to_string(),
+        }),
+    })
+}
+
+/// The highest ancestor of `name` that lives under `system`, i.e. the vendor
+/// profile it ultimately derives from; `None` when the whole chain is user
+/// defined.
+#[tauri::command]
+fn root_ancestor(name: String) -> Result<Option<String>, String> {
+    let root = ensure_installed()?;
+    let res = resolve_chain(&name, &ResolveOptions::default())?;
+    Ok(res
+        .chain
+        .into_iter()
+        .find(|l| l.path.starts_with(root.join("system")))
+        .map(|l| l.name))
+}
+
+/// How `a` and `b` are related through inheritance: the profiles on the way
+/// from `a` up to the nearest ancestor they share and back down to `b`,
+/// both ends included. When one inherits from the other the path is just
+/// the chain between them; `None` means no ancestor is shared. Profiles are
+/// told apart by file, so two ancestors with the same name under different
+/// vendors aren't confused.
+#[tauri::command]
+fn relationship(a: String, b: String) -> Result<Option<Vec<String>>, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    // leaf-first, so index 0 is the profile itself
+    let up = |name: &str| -> Result<Vec<(PathBuf, String)>, String> {
+        let chain = resolve_chain(name, &opts)?.chain;
+        Ok(chain.into_iter().rev().map(|l| (l.path, l.name)).collect())
+    };
+    let (from_a, from_b) = (up(&a)?, up(&b)?);
+    let Some((i, j)) = from_a
+        .iter()
+        .enumerate()
+        .find_map(|(i, (p, _))| Some((i, from_b.iter().position(|(q, _)| q == p)?)))
+    else {
+        return Ok(None);
+    };
+    let path = from_a[..=i]
+        .iter()
+        .chain(from_b[..j].iter().rev())
+        .map(|(_, name)| name.clone())
+        .collect();
+    Ok(Some(path))
+}
+
+/// Merges a bottom→top chain without stamping any export metadata
+fn merge_chain(chain: &[ChainLink]) -> FilamentProfile {
+    let rules = Settings::load().effective_merge_rules();
+    let mut acc = FilamentProfile::default();
+    for link in chain {
+        acc.merge_with_rules(&link.profile, &rules);
     }
     acc
 }
 
+/// Profile type implied by the folder a profile file sits in
+fn kind_from_path(path: &Path) -> Option<&'static str> {
+    let dir = path.parent()?.file_name()?;
+    ["filament", "process", "machine"]
+        .into_iter()
+        .find(|k| dir == OsStr::new(k))
+}
+
+/// The type `build_final` stamps on a chain that doesn't set one
+fn default_kind(chain: &[ChainLink]) -> &'static str {
+    chain
+        .last()
+        .and_then(|l| kind_from_path(&l.path))
+        .unwrap_or("filament")
+}
+
+fn build_final(chain: &[ChainLink], final_name: &str) -> Result<FilamentProfile, Error> {
+    build_final_as(chain, final_name, Some(default_kind(chain)))
+}
+
+/// `build_final`, with `kind` as the type to stamp when no layer sets one;
+/// `None` leaves a typeless profile typeless.
+fn build_final_as(
+    chain: &[ChainLink],
+    final_name: &str,
+    kind: Option<&str>,
+) -> Result<FilamentProfile, Error> {
+    if chain.is_empty() {
+        return Err(Error::EmptyChain(final_name.to_string()));
+    }
+    let mut acc = merge_chain(chain);
+    acc.inherits = None;
+    acc.name = Some(final_name.to_string());
+    let from = chain
+        .last()
+        .and_then(|l| l.profile.from.clone())
+        .unwrap_or_else(|| "User".into());
+    acc.from = Some(from);
+    acc.instantiation = Some("true".into());
+    if acc.kind.is_none() {
+        acc.kind = kind.map(String::from);
+    }
+    Ok(acc)
+}
+
+#[tauri::command]
+fn is_orca_installed() -> bool {
+    ensure_installed().is_ok()
+}
+
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {name}!")
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildReport {
+    name: String,
+    profile: Value,
+    warnings: Vec<String>,
+    /// Length of the profile serialized as exported, i.e. pretty-printed
+    pretty_bytes: usize,
+    /// Length of the profile serialized without whitespace
+    compact_bytes: usize,
+    /// Internal keys left out under `strip_internal`
+    stripped: Vec<String>,
+    /// Files read and folders listed while building, under `io_stats`
+    io: Option<stats::IoStats>,
+    /// Per key, the value after each layer that set it, bottom first, under
+    /// `merge_log`
+    merge_log: Option<BTreeMap<String, Vec<MergeStep>>>,
+}
+
 #[tauri::command]
-fn build_filament_profile(start: String) -> Result<String, String> {
+fn build_filament_profile_detailed(
+    start: String,
+    options: Option<ResolveOptions>,
+) -> Result<BuildReport, String> {
     println!("building profile {}", &start);
-    let chain = resolve_chain(&start)?;
+    let io_before = stats::snapshot();
+    ensure_installed()?;
+    let opts = options.unwrap_or_default().with_lookup();
+    let Resolution {
+        chain,
+        mut warnings,
+    } = resolve_chain(&start, &opts)?;
+    if chain.is_empty() {
+        return Err(Error::EmptyChain(start).into());
+    }
     let final_name = chain
         .last()
-        .map(|(n, o)| o.get("name").and_then(Value::as_str).unwrap_or(n))
-        .unwrap_or(&start);
-    let final_json = build_final(&chain, final_name);
-    serde_json::to_string_pretty(&final_json).map_err(|e| e.to_string())
+        .map(|l| l.profile.name.as_deref().unwrap_or(&l.name))
+        .unwrap_or(&start)
+        .to_string();
+
+    if opts.strict_merge {
+        let changes = value_type_changes(&chain)?;
+        if !changes.is_empty() {
+            return Err(changes.join("; "));
+        }
+    }
+
+    let (layers, keep_inherits) = match &opts.flatten_to {
+        None => (&chain[..], None),
+        Some(base) => {
+            let i = chain
+                .iter()
+                .position(|l| &l.name == base)
+                .ok_or_else(|| format!("'{}' is not an ancestor of '{}'", base, final_name))?;
+            if i + 1 == chain.len() {
+                return Err(format!("cannot flatten '{}' onto itself", base));
+            }
+            (&chain[i + 1..], Some(base.clone()))
+        }
+    };
+    let kind = match &opts.profile_type {
+        _ if opts.no_default_type => None,
+        Some(kind) => Some(kind.as_str()),
+        None => Some(default_kind(&chain)),
+    };
+    let mut built = build_final_as(layers, &final_name, kind)?;
+    built.inherits = keep_inherits;
+    let mut profile = built.to_value()?;
+    let mut stripped = Vec::new();
+    if let (true, Value::Object(map)) = (opts.strip_internal, &mut profile) {
+        let prefixes: Vec<&str> = match &opts.strip_prefixes {
+            Some(p) => p.iter().map(String::as_str).collect(),
+            None => INTERNAL_KEY_PREFIXES.to_vec(),
+        };
+        map.retain(|k, _| {
+            let internal = prefixes.iter().any(|p| !p.is_empty() && k.starts_with(p));
+            if internal {
+                stripped.push(k.clone());
+            }
+            !internal
+        });
+    }
+    if let (true, Value::Object(map)) = (opts.canonical_numbers, &mut profile) {
+        for key in normalize::canonicalize_numbers(map) {
+            warnings.push(format!(
+                "'{}' has a number whose comma could be a decimal or a thousands separator; left as is",
+                key
+            ));
+        }
+    }
+    if opts.strict {
+        if let Value::Object(map) = &profile {
+            let empty = validate::empty_required_keys(map);
+            if !empty.is_empty() {
+                return Err(format!(
+                    "'{}' has empty required keys: {}",
+                    final_name,
+                    empty.join(", ")
+                ));
+            }
+        }
+    }
+    let pretty_bytes = serde_json::to_vec_pretty(&profile)
+        .map_err(|e| e.to_string())?
+        .len();
+    let compact_bytes = serde_json::to_vec(&profile)
+        .map_err(|e| e.to_string())?
+        .len();
+    Ok(BuildReport {
+        name: final_name,
+        profile,
+        warnings,
+        pretty_bytes,
+        compact_bytes,
+        stripped,
+        io: opts.io_stats.then(|| stats::snapshot().since(io_before)),
+        merge_log: if opts.merge_log {
+            Some(merge_log(layers)?)
+        } else {
+            None
+        },
+    })
 }
 
 #[tauri::command]
-fn export_filament_profile(start: String, output_path: String) -> Result<String, String> {
-    println!("exporting filament profile {}", &start);
-    let s = build_filament_profile(start)?;
-    fs::write(&output_path, s.as_bytes())
-        .map_err(|e| format!("write {}: {}", output_path, e))?;
-    Ok(output_path)
+fn build_filament_profile(
+    start: String,
+    options: Option<ResolveOptions>,
+) -> Result<String, String> {
+    let report = build_filament_profile_detailed(start, options)?;
+    for w in &report.warnings {
+        println!("warning: {}", w);
+    }
+    to_pretty_json(&report.profile, None)
 }
 
+/// The resolved profile cut down to the top-level `keys` asked for, for
+/// callers that only need a few settings. Keys the profile doesn't have
+/// are left out.
 #[tauri::command]
-fn list_user_filament_profiles() -> Result<Vec<String>, String> {
-    use std::collections::BTreeSet;
-    let mut names: BTreeSet<String> = BTreeSet::new();
+fn build_filament_profile_fields(start: String, keys: Vec<String>) -> Result<Value, String> {
+    let report = build_filament_profile_detailed(start, None)?;
+    let Value::Object(mut profile) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", report.name));
+    };
+    let fields = keys
+        .into_iter()
+        .filter_map(|k| profile.remove_entry(&k))
+        .collect();
+    Ok(Value::Object(fields))
+}
 
-    for d in user_filament_dirs() {
-        let Ok(read) = std::fs::read_dir(&d) else { continue };
-        for e in read.flatten() {
-            let p = e.path();
-            if p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("json") {
-                // Prefer the "name" field; fallback to filename (without .json)
-                let name = load_json(&p)
-                    .ok()
-                    .and_then(|v| v.get("name").and_then(|s| s.as_str()).map(|s| s.to_string()))
-                    .or_else(|| p.file_stem().and_then(|s| s.to_str()).map(|s| s.to_string()));
-
-                if let Some(n) = name {
-                    names.insert(n);
-                }
-            }
+/// Pretty-prints `value` indented by `indent` spaces per level; `None` keeps
+/// serde_json's default of two.
+fn to_pretty_json<T: Serialize + ?Sized>(
+    value: &T,
+    indent: Option<usize>,
+) -> Result<String, String> {
+    let Some(width) = indent else {
+        return serde_json::to_string_pretty(value).map_err(|e| e.to_string());
+    };
+    let spaces = " ".repeat(width);
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(spaces.as_bytes());
+    let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut ser).map_err(|e| e.to_string())?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
+/// How lines end in an exported file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LineEnding {
+    Lf,
+    /// What OrcaSlicer writes on Windows
+    Crlf,
+}
+
+impl LineEnding {
+    /// The convention OrcaSlicer follows on the platform we run on
+    fn native() -> LineEnding {
+        if cfg!(windows) {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// `text`, written with `\n` line breaks, with its breaks made this kind.
+    /// JSON escapes newlines inside strings, so only layout breaks change.
+    fn apply(self, text: String) -> String {
+        match self {
+            LineEnding::Lf => text,
+            LineEnding::Crlf => text.replace('\n', "\r\n"),
         }
     }
-    Ok(names.into_iter().collect())
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            build_filament_profile,
-            export_filament_profile,
-            list_user_filament_profiles
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+/// Like [`build_filament_profile`], but with `pins` deciding which file an
+/// ancestor name (as written in `inherits`, or `start` itself) resolves to,
+/// whatever else is installed. Names without a pin are looked up as usual.
+/// Every pinned file must exist and parse, whether or not the chain ends up
+/// using it.
+#[tauri::command]
+fn build_filament_profile_pinned(
+    start: String,
+    pins: HashMap<String, String>,
+) -> Result<String, String> {
+    for (name, path) in &pins {
+        load_json(Path::new(path), &mut Vec::new())
+            .map_err(|e| format!("pin for '{}': {}", name, e))?;
+    }
+    let opts = ResolveOptions {
+        pins,
+        ..Default::default()
+    };
+    build_filament_profile(start, Some(opts))
+}
+
+/// Resolves `start` and merges `overrides` on top, the same way a child
+/// profile would be merged, without writing anything. Lets the UI preview
+/// edits before they are saved.
+#[tauri::command]
+fn build_filament_profile_with_overrides(start: String, overrides: Value) -> Result<Value, String> {
+    if !overrides.is_object() {
+        return Err("overrides must be a JSON object".into());
+    }
+    let overrides = FilamentProfile::try_from(overrides)?;
+    let report = build_filament_profile_detailed(start, None)?;
+    let mut built = FilamentProfile::try_from(report.profile)?;
+    built.merge_with_rules(&overrides, &Settings::load().effective_merge_rules());
+    built.to_value()
+}
+
+/// One entry of a `build_filament_profiles` batch
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildResult {
+    /// The name as requested
+    name: String,
+    profile: Option<Value>,
+    warnings: Vec<String>,
+    /// Why this profile couldn't be built; the rest of the batch still is
+    error: Option<String>,
+}
+
+/// Resolves several profiles in one round-trip. The system index, settings
+/// and user file list are shared across the batch, so only the first lookup
+/// can pay for loading them. Cancellable through `operation_id`.
+#[tauri::command]
+fn build_filament_profiles(
+    names: Vec<String>,
+    operation_id: Option<String>,
+    ops: State<'_, Cancellations>,
+) -> Result<Vec<BuildResult>, String> {
+    ensure_installed()?;
+    let op = ops.start(operation_id);
+    let opts = ResolveOptions::default().with_lookup();
+    let mut out = Vec::new();
+    for name in names {
+        op.check()?;
+        out.push(
+            match build_filament_profile_detailed(name.clone(), Some(opts.clone())) {
+                Ok(report) => BuildResult {
+                    name,
+                    profile: Some(report.profile),
+                    warnings: report.warnings,
+                    error: None,
+                },
+                Err(e) => BuildResult {
+                    name,
+                    profile: None,
+                    warnings: Vec::new(),
+                    error: Some(e),
+                },
+            },
+        );
+    }
+    Ok(out)
+}
+
+/// Expresses a fully resolved profile as overrides on top of `base_name`:
+/// only the keys whose values differ from the base, plus `inherits`.
+#[tauri::command]
+fn compute_overrides(resolved: Value, base_name: String) -> Result<Value, String> {
+    ensure_installed()?;
+    let Value::Object(target) = resolved else {
+        return Err("resolved profile must be a JSON object".into());
+    };
+    let base = resolve_chain(&base_name, &ResolveOptions::default())?;
+    let Value::Object(base) = merge_chain(&base.chain).to_value()? else {
+        return Err(format!("base '{}' did not resolve to an object", base_name));
+    };
+    let mut out = diff::overrides(&target, &base);
+    out.insert("inherits".into(), Value::String(base_name));
+    Ok(Value::Object(out))
+}
+
+/// The difference between `start` as resolved and the resolved `base`, as an
+/// RFC 6902 JSON Patch; applying it to the base yields `start`. See
+/// `diff::json_patch`.
+#[tauri::command]
+fn overrides_as_json_patch(start: String, base: String) -> Result<Value, String> {
+    let Value::Object(target) = build_filament_profile_detailed(start.clone(), None)?.profile
+    else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    let chain = resolve_chain(&base, &ResolveOptions::default())?.chain;
+    let Value::Object(base_values) = merge_chain(&chain).to_value()? else {
+        return Err(format!("base '{}' did not resolve to an object", base));
+    };
+    Ok(Value::Array(diff::json_patch(&base_values, &target)))
+}
+
+/// Side-by-side values of `keys` across the resolved `names`, for the
+/// comparison table: `{"profiles": [...], "rows": {key: [value per profile]}}`.
+/// A profile that doesn't set a key gets `null` in that slot.
+#[tauri::command]
+fn compare_profiles(names: Vec<String>, keys: Vec<String>) -> Result<Value, String> {
+    let mut resolved = Vec::new();
+    for name in &names {
+        resolved.push(build_filament_profile_detailed(name.clone(), None)?.profile);
+    }
+    let rows: serde_json::Map<String, Value> = keys
+        .iter()
+        .map(|k| {
+            let values = resolved
+                .iter()
+                .map(|p| p.get(k).cloned().unwrap_or(Value::Null))
+                .collect();
+            (k.clone(), Value::Array(values))
+        })
+        .collect();
+    Ok(serde_json::json!({ "profiles": names, "rows": rows }))
+}
+
+/// What `edited` changes relative to the file currently on disk for `name`,
+/// before any inheritance is applied; see `diff::changes`. When no file
+/// exists any more, every key of `edited` counts as added.
+#[tauri::command]
+fn diff_against_disk(name: String, edited: Value) -> Result<Value, String> {
+    ensure_installed()?;
+    let Value::Object(edited) = edited else {
+        return Err("edited profile must be a JSON object".into());
+    };
+    let path = match user_filament_profiles().remove(&name) {
+        Some(p) => Some(p),
+        None => find_profile_file(&name, None, &ResolveOptions::default(), &mut Vec::new()),
+    };
+    let on_disk = match path {
+        Some(p) => match load_json(&p, &mut Vec::new())? {
+            Value::Object(map) => map,
+            _ => return Err(format!("{} is not a JSON object", p.display())),
+        },
+        None => serde_json::Map::new(),
+    };
+    Ok(diff::changes(&on_disk, &edited))
+}
+
+/// Keys that identify a profile rather than configure it
+const IDENTITY_KEYS: [&str; 4] = ["name", "inherits", "from", "instantiation"];
+
+fn is_user_profile(path: &Path) -> bool {
+    path.starts_with(orca_root().join("user"))
+}
+
+/// Marks `out` as a user preset called `name`, the way OrcaSlicer saves one
+fn stamp_user_profile(out: &mut serde_json::Map<String, Value>, name: &str) {
+    out.insert("name".into(), Value::String(name.to_string()));
+    out.insert("from".into(), Value::String("User".into()));
+    out.insert("instantiation".into(), Value::String("true".into()));
+    // setting_id identifies the vendor preset the values came from
+    out.remove("setting_id");
+    if out.contains_key("filament_settings_id") {
+        out.insert("filament_settings_id".into(), serde_json::json!([name]));
+    }
+}
+
+/// Device names Windows won't create files for, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A file name (without extension) for the profile `name` that is valid on
+/// every platform the slicers run on. Characters illegal on Windows or macOS
+/// become `_`, as do trailing dots and spaces, which Windows strips; reserved
+/// device names get a `_` prefix. Path separators never survive, so the
+/// result can't point outside the directory it's joined to.
+pub(crate) fn sanitize_filename(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let kept = out.trim_end_matches(['.', ' ']).len();
+    let trailing = out.len() - kept;
+    out.truncate(kept);
+    out.extend(std::iter::repeat_n('_', trailing));
+    let stem = out.split('.').next().unwrap_or_default();
+    if out.is_empty() || RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Where the profile `name` should be written in `dir`: its sanitized file
+/// name, or `<name> (2).json` and so on when that file already holds a
+/// different profile, e.g. `A/B` and `A_B` both sanitizing to `A_B`. The path
+/// returned either doesn't exist yet or already holds `name`.
+fn profile_file_for(dir: &Path, name: &str) -> PathBuf {
+    profile_file_avoiding(dir, name, &[])
+}
+
+/// [`profile_file_for`] that also passes over `reserved`: paths already
+/// picked for other profiles of the same batch but not written yet.
+fn profile_file_avoiding(dir: &Path, name: &str, reserved: &[PathBuf]) -> PathBuf {
+    let base = sanitize_filename(name);
+    let holds =
+        |p: &Path| load_profile(p, &mut Vec::new()).is_ok_and(|p| p.name.as_deref() == Some(name));
+    let mut n = 1;
+    loop {
+        let fname = if n == 1 {
+            format!("{base}.json")
+        } else {
+            format!("{base} ({n}).json")
+        };
+        let path = dir.join(fname);
+        if !reserved.contains(&path) && (!path.exists() || holds(&path)) {
+            return path;
+        }
+        n += 1;
+    }
+}
+
+/// The path a profile called `name` would be written to in `dir`, with the
+/// same sanitizing and collision handling the writing commands apply.
+/// Nothing is written.
+#[tauri::command]
+fn preview_export_filename(name: String, dir: String) -> Result<String, String> {
+    let dir = Path::new(&dir);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    Ok(profile_file_for(dir, &name).display().to_string())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReparentReport {
+    path: String,
+    /// Settings the original profile set itself that the new base already matches
+    dropped: Vec<String>,
+}
+
+/// Writes `new_name` as a user profile inheriting from `new_base`, carrying over
+/// whatever `name` resolves to that differs from the new base.
+#[tauri::command]
+fn reparent_profile(
+    name: String,
+    new_base: String,
+    new_name: String,
+    undo: State<'_, UndoStack>,
+) -> Result<ReparentReport, String> {
+    ensure_writable()?;
+    println!("reparenting {} onto {} as {}", &name, &new_base, &new_name);
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let current = resolve_chain(&name, &opts)?;
+    let base = resolve_chain(&new_base, &opts)?;
+    if new_base == new_name || base.chain.iter().any(|l| l.name == new_name) {
+        return Err(format!(
+            "re-parenting onto '{}' would make '{}' inherit from itself",
+            new_base, new_name
+        ));
+    }
+
+    let Value::Object(resolved) = merge_chain(&current.chain).to_value()? else {
+        return Err(format!("'{}' did not resolve to an object", name));
+    };
+    let Value::Object(base_values) = merge_chain(&base.chain).to_value()? else {
+        return Err(format!("base '{}' did not resolve to an object", new_base));
+    };
+    let mut out = diff::overrides(&resolved, &base_values);
+
+    let leaf = current.chain.last().ok_or("empty chain")?;
+    let Value::Object(own) = leaf.profile.to_value()? else {
+        return Err(format!("'{}' is not a JSON object", name));
+    };
+    let dropped: Vec<String> = own
+        .keys()
+        .filter(|k| !IDENTITY_KEYS.contains(&k.as_str()) && !out.contains_key(*k))
+        .cloned()
+        .collect();
+
+    stamp_user_profile(&mut out, &new_name);
+    out.insert("inherits".into(), Value::String(new_base));
+
+    let dir = if is_user_profile(&leaf.path) {
+        leaf.path.parent().map(Path::to_path_buf)
+    } else {
+        user_filament_dirs().into_iter().next()
+    }
+    .ok_or("no user filament directory to write to")?;
+    let path = profile_file_for(&dir, &new_name);
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+
+    let s = serde_json::to_string_pretty(&Value::Object(out)).map_err(|e| e.to_string())?;
+    undo.record_while(&format!("reparent {}", name), &[&path], || {
+        fs::write(&path, s.as_bytes()).map_err(|e| format!("write {}: {}", path.display(), e))
+    })?;
+    Ok(ReparentReport {
+        path: path.display().to_string(),
+        dropped,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RebaseReport {
+    /// The system profile the overrides now sit on
+    base: String,
+    /// The user's own settings as a profile inheriting from `base`
+    profile: Value,
+    /// Settings the user set that the base now matches, left out of `profile`
+    redundant: Vec<String>,
+}
+
+/// Re-expresses the user layers of `name` on top of a fresh resolution of the
+/// system profile they sit on, the nearest system ancestor, for when the
+/// vendor updated it: the user's tweaks are kept and everything else comes
+/// from the base as it is now. Nothing is written; save the returned profile
+/// to apply it.
+#[tauri::command]
+fn update_to_latest_base(name: String) -> Result<RebaseReport, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let chain = resolve_chain(&name, &opts)?.chain;
+    let Some(i) = chain.iter().rposition(|l| !is_user_profile(&l.path)) else {
+        return Err(format!("'{}' doesn't derive from a system profile", name));
+    };
+    if i + 1 == chain.len() {
+        return Err(format!("'{}' is a system profile itself", name));
+    }
+    let leaf = &chain[chain.len() - 1];
+    let final_name = leaf
+        .profile
+        .name
+        .clone()
+        .unwrap_or_else(|| leaf.name.clone());
+    let base_name = chain[i].name.clone();
+
+    let Value::Object(mut own) = merge_chain(&chain[i + 1..]).to_value()? else {
+        return Err(format!("'{}' is not a JSON object", name));
+    };
+    own.retain(|k, _| !IDENTITY_KEYS.contains(&k.as_str()));
+    let base = resolve_chain(&base_name, &opts)?;
+    let Value::Object(base_values) = merge_chain(&base.chain).to_value()? else {
+        return Err(format!("base '{}' did not resolve to an object", base_name));
+    };
+    let mut out = diff::overrides(&own, &base_values);
+    let redundant = own
+        .keys()
+        .filter(|k| !out.contains_key(*k))
+        .cloned()
+        .collect();
+
+    stamp_user_profile(&mut out, &final_name);
+    out.insert("inherits".into(), Value::String(base_name.clone()));
+    Ok(RebaseReport {
+        base: base_name,
+        profile: Value::Object(out),
+        redundant,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileChange {
+    name: String,
+    path: String,
+    /// Keys that repeat what the profile inherits and can go
+    removed: Vec<String>,
+    /// Why the file was skipped; `removed` is then empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Finds, in every user profile, the keys that only restate what the profile
+/// already inherits, and with `apply` rewrites the files without them. A key
+/// is only listed if taking it out leaves the resolved profile exactly as it
+/// was, so e.g. a boolean spelled differently from the parent's stays.
+/// Profiles that don't resolve are skipped; a file that resolves but can't
+/// be read back is listed with its `error`, and the rest are still cleaned.
+///
+/// Before rewriting, each file is copied to a `backups/<timestamp>` folder
+/// in the app data directory and recorded for undo.
+#[tauri::command]
+fn clean_redundant_overrides(
+    apply: bool,
+    undo: State<'_, UndoStack>,
+) -> Result<Vec<ProfileChange>, String> {
+    ensure_installed()?;
+    if apply {
+        ensure_writable()?;
+    }
+    let opts = ResolveOptions::default();
+    let backups = backup_dir();
+    let mut changes = Vec::new();
+    for (name, path) in user_filament_profiles() {
+        let Ok(Resolution { mut chain, .. }) = resolve_chain_from(&path, &opts) else {
+            continue;
+        };
+        let mut own = match load_json(&path, &mut Vec::new()) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => continue,
+            Err(e) => {
+                changes.push(ProfileChange {
+                    name,
+                    path: path.display().to_string(),
+                    removed: Vec::new(),
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+        let resolved = merge_chain(&chain).to_value()?;
+        let Value::Object(parent) = merge_chain(&chain[..chain.len() - 1]).to_value()? else {
+            continue;
+        };
+        let overrides = diff::overrides(&own, &parent);
+        let candidates: Vec<String> = own
+            .keys()
+            .filter(|k| !IDENTITY_KEYS.contains(&k.as_str()) && !overrides.contains_key(*k))
+            .cloned()
+            .collect();
+
+        let mut removed = Vec::new();
+        for key in candidates {
+            let Some(value) = own.remove(&key) else {
+                continue;
+            };
+            let leaf = chain.len() - 1;
+            chain[leaf].profile = serde_json::from_value(Value::Object(own.clone()))
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            if merge_chain(&chain).to_value()? == resolved {
+                removed.push(key);
+            } else {
+                own.insert(key, value);
+            }
+        }
+        if removed.is_empty() {
+            continue;
+        }
+        if apply {
+            backup_profile(&path, &backups)?;
+            let s = serde_json::to_string_pretty(&Value::Object(own)).map_err(|e| e.to_string())?;
+            undo.record_while(&format!("clean {}", name), &[&path], || {
+                fs::write(&path, s.as_bytes())
+                    .map_err(|e| format!("write {}: {}", path.display(), e))
+            })?;
+        }
+        changes.push(ProfileChange {
+            name,
+            path: path.display().to_string(),
+            removed,
+            error: None,
+        });
+    }
+    Ok(changes)
+}
+
+/// The `from` values OrcaSlicer writes
+const FROM_VALUES: &[&str] = &["User", "System", "Default"];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FromFieldIssue {
+    name: String,
+    path: String,
+    /// What the file has now; `None` when `from` is missing
+    from: Option<Value>,
+}
+
+/// User profiles whose `from` is missing or isn't one of `FROM_VALUES`
+/// exactly, and with `apply` rewrites them to say `"User"`. Only files in
+/// the user folders are looked at; before rewriting, each is copied to a
+/// `backups/<timestamp>` folder in the app data directory and recorded for
+/// undo.
+#[tauri::command]
+fn normalize_from_fields(
+    apply: bool,
+    undo: State<'_, UndoStack>,
+) -> Result<Vec<FromFieldIssue>, String> {
+    ensure_installed()?;
+    if apply {
+        ensure_writable()?;
+    }
+    let backups = backup_dir();
+    let mut issues = Vec::new();
+    for (name, path) in user_filament_profiles() {
+        if !is_user_profile(&path) {
+            continue;
+        }
+        let Ok(Value::Object(mut own)) = load_json(&path, &mut Vec::new()) else {
+            continue;
+        };
+        let from = own.get("from").cloned();
+        if from
+            .as_ref()
+            .and_then(Value::as_str)
+            .is_some_and(|f| FROM_VALUES.contains(&f))
+        {
+            continue;
+        }
+        if apply {
+            backup_profile(&path, &backups)?;
+            own.insert("from".into(), Value::String("User".into()));
+            let s = serde_json::to_string_pretty(&Value::Object(own)).map_err(|e| e.to_string())?;
+            undo.record_while(&format!("fix from of {}", name), &[&path], || {
+                fs::write(&path, s.as_bytes())
+                    .map_err(|e| format!("write {}: {}", path.display(), e))
+            })?;
+        }
+        issues.push(FromFieldIssue {
+            name,
+            path: path.display().to_string(),
+            from,
+        });
+    }
+    Ok(issues)
+}
+
+/// A fresh `backups/<unix seconds>` folder path under the app data directory
+fn backup_dir() -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    settings::app_config_dir()
+        .join("backups")
+        .join(stamp.to_string())
+}
+
+/// Copies `path` into `dir`, keeping its file name, and returns the copy.
+fn backup_profile(path: &Path, dir: &Path) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    let dst = dir.join(path.file_name().unwrap_or_default());
+    fs::copy(path, &dst).map_err(|e| format!("back up {}: {}", path.display(), e))?;
+    Ok(dst)
+}
+
+/// Copies a system profile into the first user filament dir as `new_name`,
+/// so it can be edited. By default the copy is flattened; with `flatten` set
+/// to false it keeps only the system profile's own keys and its `inherits`.
+#[tauri::command]
+fn fork_system_profile(
+    name: String,
+    new_name: String,
+    flatten: Option<bool>,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("forking {} as {}", &name, &new_name);
+    ensure_installed()?;
+    let res = resolve_chain(&name, &ResolveOptions::default())?;
+    let leaf = res.chain.last().ok_or("empty chain")?;
+    if is_user_profile(&leaf.path) {
+        return Err(format!("'{}' is already a user profile", name));
+    }
+    if user_filament_profiles().contains_key(&new_name) {
+        return Err(format!(
+            "a user profile named '{}' already exists",
+            new_name
+        ));
+    }
+
+    let profile = if flatten.unwrap_or(true) {
+        build_final(&res.chain, &new_name)?
+    } else {
+        leaf.profile.clone()
+    };
+    let Value::Object(mut out) = profile.to_value()? else {
+        return Err(format!("'{}' did not resolve to an object", name));
+    };
+    stamp_user_profile(&mut out, &new_name);
+
+    let dir = user_filament_dirs()
+        .into_iter()
+        .next()
+        .ok_or("no user filament directory to write to")?;
+    let path = profile_file_for(&dir, &new_name);
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    let s = serde_json::to_string_pretty(&Value::Object(out)).map_err(|e| e.to_string())?;
+    undo.record_while(&format!("fork {}", name), &[&path], || {
+        fs::write(&path, s.as_bytes()).map_err(|e| format!("write {}: {}", path.display(), e))
+    })?;
+    Ok(path.display().to_string())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MergeStep {
+    ancestor: String,
+    /// The key's merged value once this layer was applied
+    value: Value,
+}
+
+/// Replays [`merge_chain`] and records, for each top-level key, the value it
+/// held after every layer that sets it. Unlike [`provenance`] this keeps the
+/// whole history, so a value that gets set and then reset shows both layers.
+fn merge_log(chain: &[ChainLink]) -> Result<BTreeMap<String, Vec<MergeStep>>, String> {
+    let rules = Settings::load().effective_merge_rules();
+    let mut acc = FilamentProfile::default();
+    let mut log: BTreeMap<String, Vec<MergeStep>> = BTreeMap::new();
+    for link in chain {
+        acc.merge_with_rules(&link.profile, &rules);
+        let (Value::Object(own), Value::Object(merged)) =
+            (link.profile.to_value()?, acc.to_value()?)
+        else {
+            continue;
+        };
+        for k in own.keys() {
+            log.entry(k.clone()).or_default().push(MergeStep {
+                ancestor: link.name.clone(),
+                value: merged.get(k).cloned().unwrap_or(Value::Null),
+            });
+        }
+    }
+    Ok(log)
+}
+
+/// Why `key` (dotted for nested keys) resolves the way it does for `start`:
+/// `{"key", "value", "contributions": [{"ancestor", "value"}]}` with the
+/// resolved value (`null` if unset) and each layer that sets the key in its
+/// own file, bottom first, so the last one is usually the winner.
+#[tauri::command]
+fn explain_key(start: String, key: String) -> Result<Value, String> {
+    ensure_installed()?;
+    let chain = resolve_chain(&start, &ResolveOptions::default())?.chain;
+    let leaf = chain.last().ok_or("empty chain")?;
+    let name = leaf
+        .profile
+        .name
+        .clone()
+        .unwrap_or_else(|| leaf.name.clone());
+    let resolved = build_final(&chain, &name)?.to_value()?;
+    let mut contributions = Vec::new();
+    for link in &chain {
+        if let Some(v) = value_at_path(&link.profile.to_value()?, &key) {
+            contributions.push(serde_json::json!({ "ancestor": link.name, "value": v }));
+        }
+    }
+    Ok(serde_json::json!({
+        "key": key,
+        "value": value_at_path(&resolved, &key).cloned().unwrap_or(Value::Null),
+        "contributions": contributions,
+    }))
+}
+
+fn json_kind(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Keys of a bottom→top chain that a layer sets to a different JSON type
+/// than the nearest layer below that sets them, one message each.
+fn value_type_changes(chain: &[ChainLink]) -> Result<Vec<String>, String> {
+    let mut below: BTreeMap<String, (&str, &'static str)> = BTreeMap::new();
+    let mut out = Vec::new();
+    for link in chain {
+        let Value::Object(own) = link.profile.to_value()? else {
+            continue;
+        };
+        for (key, v) in &own {
+            let kind = json_kind(v);
+            if let Some((parent, parent_kind)) = below.get(key) {
+                if *parent_kind != kind {
+                    out.push(format!(
+                        "'{}' is a {} in '{}' but a {} in '{}'",
+                        key, parent_kind, parent, kind, link.name
+                    ));
+                }
+            }
+            below.insert(key.clone(), (&link.name, kind));
+        }
+    }
+    Ok(out)
+}
+
+/// For each top-level key of the merged chain, the name of the last layer that set it
+fn provenance(chain: &[ChainLink]) -> Result<BTreeMap<String, String>, String> {
+    let mut origin = BTreeMap::new();
+    for link in chain {
+        if let Value::Object(map) = link.profile.to_value()? {
+            for k in map.keys() {
+                origin.insert(k.clone(), link.name.clone());
+            }
+        }
+    }
+    Ok(origin)
+}
+
+/// Writes the resolved profile as JSON5 with a comment above each key naming
+/// the ancestor that set it. OrcaSlicer can't read this; it's for review.
+#[tauri::command]
+fn export_annotated(
+    start: String,
+    output_path: String,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("exporting annotated profile {}", &start);
+    ensure_installed()?;
+    let Resolution { chain, .. } = resolve_chain(&start, &ResolveOptions::default())?;
+    let leaf = chain.last().ok_or("empty chain")?;
+    let origin = provenance(&chain)?;
+    let merged = merge_chain(&chain).to_value()?;
+    let Value::Object(resolved) = build_final(&chain, &leaf.name)?.to_value()? else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+
+    let mut out = format!(
+        "// {}, resolved by orca-exporter; each comment names the profile that set the key\n{{\n",
+        leaf.name
+    );
+    let last = resolved.len().saturating_sub(1);
+    for (i, (k, v)) in resolved.iter().enumerate() {
+        let comment = match origin.get(k) {
+            Some(who) if merged.get(k) == Some(v) => format!("from '{}'", who),
+            _ => "set on export".to_string(),
+        };
+        let key = serde_json::to_string(k).map_err(|e| e.to_string())?;
+        let value = serde_json::to_string_pretty(v)
+            .map_err(|e| e.to_string())?
+            .replace('\n', "\n  ");
+        let comma = if i == last { "" } else { "," };
+        out.push_str(&format!(
+            "  // {}\n  {}: {}{}\n",
+            comment, key, value, comma
+        ));
+    }
+    out.push_str("}\n");
+
+    undo.record_while(
+        &format!("export annotated {}", start),
+        &[Path::new(&output_path)],
+        || {
+            fs::write(&output_path, out.as_bytes())
+                .map_err(|e| format!("write {}: {}", output_path, e))
+        },
+    )?;
+    Settings::update(|s| s.push_recent(&start))?;
+    Ok(output_path)
+}
+
+/// Writes the resolved profile to `output_path`. Lines end the way
+/// OrcaSlicer writes them on this platform unless `line_ending` says
+/// otherwise. With `verify` set the file is read back afterwards, as in
+/// `verify_export`, and a discrepancy is an error.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn export_filament_profile(
+    start: String,
+    output_path: String,
+    options: Option<ResolveOptions>,
+    indent: Option<usize>,
+    color: Option<String>,
+    line_ending: Option<LineEnding>,
+    verify: Option<bool>,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    write_filament_profile(
+        start,
+        output_path,
+        options,
+        ExportFormat {
+            indent,
+            color,
+            line_ending: line_ending.unwrap_or_else(LineEnding::native),
+            verify: verify.unwrap_or(false),
+        },
+        &undo,
+        read_only(),
+    )
+}
+
+/// How `write_filament_profile` lays out and checks the file it writes
+struct ExportFormat {
+    indent: Option<usize>,
+    color: Option<String>,
+    line_ending: LineEnding,
+    verify: bool,
+}
+
+fn write_filament_profile(
+    start: String,
+    output_path: String,
+    options: Option<ResolveOptions>,
+    format: ExportFormat,
+    undo: &UndoStack,
+    read_only: bool,
+) -> Result<String, String> {
+    ensure_writable_with(read_only)?;
+    let color = format.color.as_deref().map(hex_color).transpose()?;
+    println!("exporting filament profile {}", &start);
+    let mut report = build_filament_profile_detailed(start.clone(), options)?;
+    for w in &report.warnings {
+        println!("warning: {}", w);
+    }
+    if let (Some(color), Value::Object(map)) = (color, &mut report.profile) {
+        map.insert(
+            "default_filament_colour".into(),
+            Value::Array(vec![Value::String(color)]),
+        );
+    }
+    let s = format
+        .line_ending
+        .apply(to_pretty_json(&report.profile, format.indent)?);
+    undo.record_while(
+        &format!("export {}", start),
+        &[Path::new(&output_path)],
+        || {
+            fs::write(&output_path, s.as_bytes())
+                .map_err(|e| format!("write {}: {}", output_path, e))
+        },
+    )?;
+    if format.verify {
+        let field = |k: &str| report.profile.get(k).and_then(Value::as_str);
+        check_export(
+            Path::new(&output_path),
+            field("name").unwrap_or(&report.name),
+            field("inherits"),
+        )?;
+    }
+    let dir = Path::new(&output_path)
+        .parent()
+        .map(|d| d.to_string_lossy().into_owned());
+    Settings::update(|s| {
+        if dir.is_some() {
+            s.last_export_dir = dir;
+        }
+        s.push_recent(&start);
+    })?;
+    Ok(output_path)
+}
+
+/// Reads back an exported profile and confirms it's what OrcaSlicer will
+/// import: readable UTF-8, JSON throughout, named `expected_name`, marked
+/// `instantiation: "true"`, and with no `inherits` since a full export is
+/// flattened. A discrepancy is the error.
+#[tauri::command]
+fn verify_export(path: String, expected_name: String) -> Result<bool, String> {
+    check_export(Path::new(&path), &expected_name, None)?;
+    Ok(true)
+}
+
+/// [`verify_export`], with `inherits` expected to be exactly `inherits`,
+/// for exports flattened only down to an ancestor.
+fn check_export(path: &Path, expected_name: &str, inherits: Option<&str>) -> Result<(), String> {
+    let bytes = fs::read(path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|e| format!("{} isn't valid UTF-8: {}", path.display(), e))?;
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| format!("{} doesn't parse back: {}", path.display(), e))?;
+    let Value::Object(map) = value else {
+        return Err(format!("{} doesn't hold a JSON object", path.display()));
+    };
+    let field = |k: &str| {
+        map.get(k)
+            .map(|v| v.as_str().map_or_else(|| v.to_string(), String::from))
+    };
+    match field("name") {
+        Some(n) if n == expected_name => {}
+        Some(n) => return Err(format!("name is '{}', expected '{}'", n, expected_name)),
+        None => return Err("name is missing".into()),
+    }
+    match field("instantiation").as_deref() {
+        Some("true") => {}
+        Some(v) => return Err(format!("instantiation is '{}', expected 'true'", v)),
+        None => return Err("instantiation is missing".into()),
+    }
+    match (field("inherits"), inherits) {
+        (None, None) => {}
+        (Some(found), Some(want)) if found == want => {}
+        (Some(found), None) => return Err(format!("still inherits from '{}'", found)),
+        (found, Some(want)) => {
+            return Err(format!(
+                "inherits {}, expected '{}'",
+                found.map_or("nothing".into(), |f| format!("'{}'", f)),
+                want
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// `#RRGGBB` or `#RRGGBBAA` (the `#` optional) spelled the way OrcaSlicer
+/// writes colours, uppercase with the `#`.
+fn hex_color(s: &str) -> Result<String, String> {
+    let digits = s.trim().trim_start_matches('#');
+    if matches!(digits.len(), 6 | 8) && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(format!("#{}", digits.to_ascii_uppercase()))
+    } else {
+        Err(format!("'{}' is not a hex colour like #FF8800", s))
+    }
+}
+
+/// Exports the resolved profile with every key that merely repeats
+/// OrcaSlicer's built-in default left out, which OrcaSlicer fills back in on
+/// import. Unlike an override diff this is measured against the global
+/// defaults, not the parent, so the result is still self-contained. Keys with
+/// no known default, and the identity keys, are always kept.
+#[tauri::command]
+fn export_nondefault(
+    start: String,
+    output_path: String,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("exporting non-default values of {}", &start);
+    let report = build_filament_profile_detailed(start.clone(), None)?;
+    let Value::Object(mut map) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    let kind = map
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("filament")
+        .to_string();
+    if let Some(table) = defaults::for_type(&kind) {
+        map.retain(|k, v| {
+            IDENTITY_KEYS.contains(&k.as_str())
+                || k == "type"
+                || !table.get(k).is_some_and(|d| normalize::same_value(k, v, d))
+        });
+    }
+    let s = serde_json::to_string_pretty(&Value::Object(map)).map_err(|e| e.to_string())?;
+    undo.record_while(
+        &format!("export {}", start),
+        &[Path::new(&output_path)],
+        || {
+            fs::write(&output_path, s.as_bytes())
+                .map_err(|e| format!("write {}: {}", output_path, e))
+        },
+    )?;
+    Settings::update(|s| s.push_recent(&start))?;
+    Ok(output_path)
+}
+
+/// The printer profiles the active slicer knows, system ones first: every
+/// profile file in a `machine` folder of the system tree or a user directory.
+fn printer_files() -> Vec<PathBuf> {
+    let extensions = profile_extensions();
+    let in_machine_dir = |p: &Path| {
+        p.parent().and_then(Path::file_name) == Some(OsStr::new("machine"))
+            && has_profile_extension(p, &extensions)
+    };
+    let (index, _) = index::system_index(&orca_root().join("system"), DEFAULT_SEARCH_TIMEOUT);
+    let mut files: Vec<PathBuf> = index
+        .paths()
+        .filter(|p| in_machine_dir(p))
+        .cloned()
+        .collect();
+    if let Ok(users) = fs::read_dir(orca_root().join("user")) {
+        for u in users.flatten() {
+            if let Ok(entries) = fs::read_dir(u.path().join("machine")) {
+                files.extend(
+                    entries
+                        .flatten()
+                        .map(|e| e.path())
+                        .filter(|p| in_machine_dir(p)),
+                );
+            }
+        }
+    }
+    files
+}
+
+/// The printer profile `printer` with its inheritance resolved, which is what
+/// `compatible_printers_condition` is evaluated against
+fn resolve_printer(printer: &str) -> Result<serde_json::Map<String, Value>, String> {
+    let path = printer_files()
+        .into_iter()
+        .find(|p| p.file_stem() == Some(OsStr::new(printer)))
+        .ok_or_else(|| format!("no printer profile named '{}'", printer))?;
+    let chain = resolve_chain_from(&path, &ResolveOptions::default())?.chain;
+    match merge_chain(&chain).to_value()? {
+        Value::Object(map) => Ok(map),
+        _ => Err(format!(
+            "printer '{}' did not resolve to an object",
+            printer
+        )),
+    }
+}
+
+/// The resolved `compatible_printers`; empty means no restriction
+fn listed_printers(profile: &serde_json::Map<String, Value>) -> Vec<&str> {
+    profile
+        .get("compatible_printers")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Whether a resolved profile may be used with `printer`, `printer_values`
+/// being that printer resolved. As in OrcaSlicer, a non-empty
+/// `compatible_printers` decides on its own; its entries may use `*` and
+/// `?`, matched as in `wildcard_match`. Otherwise `compatible_printers_condition`
+/// does, when set; an expression `condition::evaluate` can't handle is an
+/// error rather than a no.
+fn compatible_with(
+    profile: &serde_json::Map<String, Value>,
+    printer: &str,
+    printer_values: &serde_json::Map<String, Value>,
+) -> Result<bool, String> {
+    let listed = listed_printers(profile);
+    if !listed.is_empty() {
+        return Ok(listed.iter().any(|p| wildcard_match(p, printer)));
+    }
+    match profile
+        .get("compatible_printers_condition")
+        .and_then(Value::as_str)
+    {
+        Some(cond) if !cond.trim().is_empty() => condition::evaluate(cond, printer_values),
+        _ => Ok(true),
+    }
+}
+
+/// A profile whose compatibility couldn't be decided
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UndecidedProfile {
+    profile: String,
+    error: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrinterListing {
+    profiles: Vec<String>,
+    /// Profiles whose `compatible_printers_condition` couldn't be evaluated
+    undecided: Vec<UndecidedProfile>,
+}
+
+/// User filament profiles that resolve to be compatible with `printer`; see
+/// `compatible_with`. Profiles that don't resolve are left out; those whose
+/// condition can't be evaluated are listed under `undecided`.
+#[tauri::command]
+fn list_profiles_for_printer(printer: String) -> Result<PrinterListing, String> {
+    ensure_installed()?;
+    let printer_values = resolve_printer(&printer)?;
+    let opts = ResolveOptions::default();
+    let mut listing = PrinterListing {
+        profiles: Vec::new(),
+        undecided: Vec::new(),
+    };
+    for (name, path) in user_filament_profiles() {
+        let resolved = resolve_chain_from(&path, &opts)
+            .map_err(String::from)
+            .and_then(|res| build_final(&res.chain, &name)?.to_value());
+        let Ok(Value::Object(profile)) = resolved else {
+            continue;
+        };
+        match compatible_with(&profile, &printer, &printer_values) {
+            Ok(true) => listing.profiles.push(name),
+            Ok(false) => {}
+            Err(error) => listing.undecided.push(UndecidedProfile {
+                profile: name,
+                error,
+            }),
+        }
+    }
+    Ok(listing)
+}
+
+/// User filament profiles restricted, by `compatible_printers` or
+/// `compatible_printers_condition`, to printers none of which are installed;
+/// see `compatible_with`. Unrestricted profiles, ones that don't resolve, and
+/// ones whose condition can't be evaluated for some printer aren't listed,
+/// nor is anything when no printer profiles are installed at all.
+#[tauri::command]
+fn list_uninstallable_profiles() -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    let mut printers: Vec<String> = printer_files()
+        .iter()
+        .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    printers.sort();
+    printers.dedup();
+    if printers.is_empty() {
+        return Ok(Vec::new());
+    }
+    // resolved on first use, since most profiles only list printers by name
+    let mut resolved_printers: HashMap<String, Result<serde_json::Map<String, Value>, String>> =
+        HashMap::new();
+
+    let opts = ResolveOptions::default();
+    let mut dead = Vec::new();
+    'profiles: for (name, path) in user_filament_profiles() {
+        let resolved = resolve_chain_from(&path, &opts)
+            .map_err(String::from)
+            .and_then(|res| build_final(&res.chain, &name)?.to_value());
+        let Ok(Value::Object(profile)) = resolved else {
+            continue;
+        };
+        let restricted = !listed_printers(&profile).is_empty()
+            || profile
+                .get("compatible_printers_condition")
+                .and_then(Value::as_str)
+                .is_some_and(|c| !c.trim().is_empty());
+        if !restricted {
+            continue;
+        }
+        for printer in &printers {
+            let values = resolved_printers
+                .entry(printer.clone())
+                .or_insert_with(|| resolve_printer(printer));
+            let empty = serde_json::Map::new();
+            let values = match values {
+                Ok(values) => values,
+                // the values only matter to the condition, which can't be
+                // decided for this printer then
+                Err(_) if listed_printers(&profile).is_empty() => continue 'profiles,
+                Err(_) => &empty,
+            };
+            if compatible_with(&profile, printer, values) != Ok(false) {
+                continue 'profiles;
+            }
+        }
+        dead.push(name);
+    }
+    Ok(dead)
+}
+
+/// Exports the resolved profile with `compatible_printers` narrowed to just
+/// `printer`, for sharing with someone who owns that machine. Fails when the
+/// printer is unknown or the profile isn't compatible with it; see
+/// `compatible_with`.
+#[tauri::command]
+fn export_for_printer(
+    start: String,
+    printer: String,
+    output_path: String,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("exporting filament profile {} for {}", &start, &printer);
+    ensure_installed()?;
+    let printer_values = resolve_printer(&printer)?;
+    let report = build_filament_profile_detailed(start.clone(), None)?;
+    let Value::Object(mut out) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    if !compatible_with(&out, &printer, &printer_values)? {
+        let listed = listed_printers(&out);
+        let why = if listed.is_empty() {
+            "its compatible_printers_condition excludes it".to_string()
+        } else {
+            format!("it lists {}", listed.join(", "))
+        };
+        return Err(format!(
+            "'{}' isn't compatible with '{}'; {}",
+            report.name, printer, why
+        ));
+    }
+    out.insert("compatible_printers".into(), serde_json::json!([printer]));
+    // OrcaSlicer only consults the condition when the list is empty
+    out.insert(
+        "compatible_printers_condition".into(),
+        Value::String(String::new()),
+    );
+
+    let s = serde_json::to_string_pretty(&Value::Object(out)).map_err(|e| e.to_string())?;
+    undo.record_while(
+        &format!("export {}", start),
+        &[Path::new(&output_path)],
+        || {
+            fs::write(&output_path, s.as_bytes())
+                .map_err(|e| format!("write {}: {}", output_path, e))
+        },
+    )?;
+    Settings::update(|s| s.push_recent(&start))?;
+    Ok(output_path)
+}
+
+/// Event `export_all_resolved` emits after each profile it handled
+const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportProgress {
+    /// Profiles handled so far, this one included
+    done: usize,
+    total: usize,
+    name: String,
+    ok: bool,
+}
+
+/// Writes `v` pretty-printed as if nested `depth` levels into a larger
+/// pretty-printed document, so pieces streamed out one by one read the same
+/// as the whole document serialized at once.
+fn write_nested(out: &mut impl Write, v: &Value, depth: usize) -> std::io::Result<()> {
+    let s = serde_json::to_string_pretty(v).map_err(std::io::Error::other)?;
+    let indent = "  ".repeat(depth);
+    for (i, line) in s.lines().enumerate() {
+        if i > 0 {
+            out.write_all(b"\n")?;
+            out.write_all(indent.as_bytes())?;
+        }
+        out.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Resolves every user filament profile into one file at `output_path`:
+/// `{"profiles": [...], "errors": [{"name", "path", "error"}]}`. Profiles that
+/// fail to resolve are listed under `errors` instead of aborting the export.
+///
+/// Each profile is written out as soon as it is resolved, so memory stays
+/// bounded however large the library, and an `export-progress` event fires
+/// after each one. The output goes to `<output_path>.part` first and only
+/// replaces `output_path` once complete: when cancelled through
+/// `operation_id`, or on error, the partial file is removed and whatever was
+/// at `output_path` before is left as it was.
+#[tauri::command]
+fn export_all_resolved(
+    output_path: String,
+    operation_id: Option<String>,
+    app: AppHandle,
+    undo: State<'_, UndoStack>,
+    ops: State<'_, Cancellations>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("exporting all profiles to {}", &output_path);
+    ensure_installed()?;
+    let op = ops.start(operation_id);
+    let part = PathBuf::from(format!("{}.part", output_path));
+    let written = stream_all_resolved(&part, &app, &op).and_then(|()| {
+        undo.record_while("export all profiles", &[Path::new(&output_path)], || {
+            fs::rename(&part, &output_path).map_err(|e| format!("write {}: {}", output_path, e))
+        })
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&part);
+    }
+    written.map(|()| output_path)
+}
+
+/// The streaming half of `export_all_resolved`, writing into `path`
+fn stream_all_resolved(
+    path: &Path,
+    app: &AppHandle,
+    op: &cancel::Operation<'_>,
+) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("write {}: {}", path.display(), e);
+    let file = fs::File::create(path).map_err(io_err)?;
+    let mut out = std::io::BufWriter::new(file);
+    let opts = ResolveOptions::default();
+    let profiles = user_filament_profiles();
+    let total = profiles.len();
+    let mut written = 0;
+    let mut errors = Vec::new();
+
+    out.write_all(b"{\n  \"profiles\": [").map_err(io_err)?;
+    for (i, (name, path)) in profiles.into_iter().enumerate() {
+        op.check()?;
+        let built = resolve_chain_from(&path, &opts)
+            .map_err(String::from)
+            .and_then(|res| build_final(&res.chain, &name)?.to_value());
+        let ok = built.is_ok();
+        match built {
+            Ok(profile) => {
+                out.write_all(if written == 0 { b"\n    " } else { b",\n    " })
+                    .and_then(|()| write_nested(&mut out, &profile, 2))
+                    .map_err(io_err)?;
+                written += 1;
+            }
+            Err(e) => errors.push(serde_json::json!({
+                "name": name,
+                "path": path.display().to_string(),
+                "error": e,
+            })),
+        }
+        let _ = app.emit(
+            EXPORT_PROGRESS_EVENT,
+            ExportProgress {
+                done: i + 1,
+                total,
+                name,
+                ok,
+            },
+        );
+    }
+    if written > 0 {
+        out.write_all(b"\n  ").map_err(io_err)?;
+    }
+    out.write_all(b"],\n  \"errors\": ")
+        .and_then(|()| write_nested(&mut out, &Value::Array(errors), 1))
+        .and_then(|()| out.write_all(b"\n}"))
+        .and_then(|()| out.flush())
+        .map_err(io_err)
+}
+
+/// Writes the resolved profile into `user/<user_id>/filament`, or the first
+/// user filament dir when no id is given, so OrcaSlicer lists it. Only an
+/// earlier install of the same profile is replaced; see `is_installed_copy`.
+/// A profile of the user's own with that name is refused rather than
+/// overwritten.
+#[tauri::command]
+fn install_profile(
+    start: String,
+    user_id: Option<String>,
+    undo: State<'_, UndoStack>,
+) -> Result<String, String> {
+    ensure_writable()?;
+    println!("installing filament profile {}", &start);
+    let root = ensure_installed()?;
+    let report = build_filament_profile_detailed(start.clone(), None)?;
+    let Value::Object(mut out) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    stamp_user_profile(&mut out, &report.name);
+
+    let dir = match user_id {
+        Some(id) => {
+            if id.is_empty() || id.contains(['/', '\\']) || id == "." || id == ".." {
+                return Err(format!("invalid user id '{}'", id));
+            }
+            let user = root.join("user").join(&id);
+            if !user.is_dir() {
+                return Err(format!("no user directory for '{}'", id));
+            }
+            user.join("filament")
+        }
+        None => user_filament_dirs()
+            .into_iter()
+            .next()
+            .ok_or("no user filament directory to write to")?,
+    };
+    let path = profile_file_for(&dir, &report.name);
+    if path.exists() && !is_installed_copy(&path) {
+        return Err(format!(
+            "{} is a user profile named '{}', not an earlier install; rename it first",
+            path.display(),
+            report.name
+        ));
+    }
+
+    let s = serde_json::to_string_pretty(&Value::Object(out)).map_err(|e| e.to_string())?;
+    undo.record_while(&format!("install {}", start), &[&path], || {
+        fs::create_dir_all(&dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        fs::write(&path, s.as_bytes()).map_err(|e| format!("write {}: {}", path.display(), e))
+    })?;
+    Settings::update(|s| s.push_recent(&start))?;
+    Ok(path.display().to_string())
+}
+
+/// Whether `path` was written by `install_profile`: stamped by
+/// `stamp_user_profile` and flattened, so without `inherits`. A profile the
+/// user made in the slicer inherits from the preset it was saved from.
+fn is_installed_copy(path: &Path) -> bool {
+    let Ok(Value::Object(map)) = load_json(path, &mut Vec::new()) else {
+        return false;
+    };
+    let is = |key: &str, value: &str| map.get(key).and_then(Value::as_str) == Some(value);
+    is("from", "User")
+        && is("instantiation", "true")
+        && !map.contains_key("inherits")
+        && !map.contains_key("setting_id")
+}
+
+/// Every lookup resolving `start` took, in order: which file each name in
+/// the chain was taken from and how, the candidates a search weighed, and
+/// the steps that found nothing and fell back to the next method. A chain
+/// that breaks still returns the steps up to and including the failed one.
+#[tauri::command]
+fn resolution_trace(start: String) -> Result<Vec<TraceStep>, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let (_, steps) = trace::capture(|| resolve_chain(&start, &opts));
+    Ok(steps)
+}
+
+/// Every user filament profile as name → file; the first file wins when two share a name
+fn user_filament_profiles() -> BTreeMap<String, PathBuf> {
+    profiles_by_name(user_profile_files(), &mut Vec::new())
+}
+
+/// Names `files` by their `name` field, else their file stem; a stem that
+/// isn't valid UTF-8 is converted lossily, with a warning.
+fn profiles_by_name(files: Vec<PathBuf>, warnings: &mut Vec<String>) -> BTreeMap<String, PathBuf> {
+    let mut found = BTreeMap::new();
+
+    for p in files {
+        // Prefer the "name" field; fallback to filename (without .json)
+        let name = load_profile(&p, &mut Vec::new())
+            .ok()
+            .and_then(|profile| profile.name)
+            .or_else(|| {
+                let stem = p.file_stem()?;
+                if stem.to_str().is_none() {
+                    warnings.push(format!(
+                        "{} isn't valid UTF-8; listing it as '{}'",
+                        p.display(),
+                        stem.to_string_lossy()
+                    ));
+                }
+                Some(stem.to_string_lossy().into_owned())
+            });
+
+        if let Some(n) = name {
+            found.entry(n).or_insert(p);
+        }
+    }
+    found
+}
+
+#[tauri::command]
+fn list_user_filament_profiles() -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    Ok(user_filament_profiles().into_keys().collect())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UserListing {
+    profiles: Vec<String>,
+    /// Folders that couldn't be read, so their profiles are missing above
+    failed_dirs: Vec<DirError>,
+    /// E.g. file names that had to be converted to be listed
+    warnings: Vec<String>,
+}
+
+/// [`list_user_filament_profiles`] plus the folders that couldn't be read,
+/// e.g. for lack of permissions, and warnings about names listed lossily.
+#[tauri::command]
+fn list_user_filament_profiles_checked() -> Result<UserListing, String> {
+    ensure_installed()?;
+    let (files, failed_dirs) = scan_user_profiles();
+    let mut warnings = Vec::new();
+    let profiles = profiles_by_name(files, &mut warnings).into_keys().collect();
+    Ok(UserListing {
+        profiles,
+        failed_dirs,
+        warnings,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileSummary {
+    name: String,
+    path: String,
+    favorite: bool,
+}
+
+#[tauri::command]
+fn list_user_filament_profiles_detailed() -> Result<Vec<ProfileSummary>, String> {
+    ensure_installed()?;
+    let favorites = Settings::load().favorites;
+    Ok(user_filament_profiles()
+        .into_iter()
+        .map(|(name, path)| ProfileSummary {
+            favorite: favorites.contains(&name),
+            path: path.display().to_string(),
+            name,
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Page {
+    profiles: Vec<ProfileSummary>,
+    /// How many profiles there are across all pages
+    total: usize,
+}
+
+/// One page of [`list_user_filament_profiles_detailed`], sorted by `sort_by`:
+/// `name`, `type` (the filament type), `vendor` or `modified` (newest first).
+/// Profiles missing the sort value go last; ties are broken by name and then
+/// path, so the order is the same on every page. Only sorting by type or
+/// vendor resolves the chains.
+#[tauri::command]
+fn list_filament_profiles_page(
+    offset: usize,
+    limit: usize,
+    sort_by: String,
+) -> Result<Page, String> {
+    ensure_installed()?;
+    if !["name", "type", "vendor", "modified"].contains(&sort_by.as_str()) {
+        return Err(format!("can't sort by '{}'", sort_by));
+    }
+    let favorites = Settings::load().favorites;
+    let profiles = user_filament_profiles();
+    let opts = ResolveOptions::default();
+    // None sorts after every Some, via the leading bool
+    let sort_key = |name: &str, path: &Path| {
+        let value = match sort_by.as_str() {
+            "type" => overview(name.to_string(), path, &opts).filament_type,
+            "vendor" => overview(name.to_string(), path, &opts).vendor,
+            "modified" => fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                // inverted so newer files sort first
+                .map(|d| format!("{:020}", u64::MAX - d.as_secs())),
+            _ => Some(name.to_string()),
+        };
+        (value.is_none(), value.unwrap_or_default().to_lowercase())
+    };
+    let mut keyed: Vec<_> = profiles
+        .into_iter()
+        .map(|(name, path)| (sort_key(&name, &path), name, path))
+        .collect();
+    keyed.sort();
+
+    let total = keyed.len();
+    let profiles = keyed
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, name, path)| ProfileSummary {
+            favorite: favorites.contains(&name),
+            path: path.display().to_string(),
+            name,
+        })
+        .collect();
+    Ok(Page { profiles, total })
+}
+
+/// User profiles whose file was modified after `timestamp` (unix seconds),
+/// most recently modified first. Files whose time can't be read are left out.
+#[tauri::command]
+fn list_profiles_modified_since(timestamp: i64) -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    let mut changed: Vec<(Duration, String)> = user_filament_profiles()
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+            (since_epoch.as_secs_f64() > timestamp as f64).then_some((since_epoch, name))
+        })
+        .collect();
+    // newest first, then by name
+    changed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(changed.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Vendor of a profile as declared by its resolved `filament_vendor`
+fn profile_vendor(chain: &[ChainLink]) -> Option<String> {
+    chain
+        .iter()
+        .rev()
+        .find_map(|l| l.profile.filament_vendor.as_ref()?.first().cloned())
+        .filter(|v| !v.is_empty())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileOverview {
+    name: String,
+    filament_type: Option<String>,
+    vendor: Option<String>,
+    color: Option<String>,
+    /// Set when the chain doesn't resolve; the fields above then only come
+    /// from the profile's own file
+    broken: bool,
+    error: Option<String>,
+}
+
+/// The first value `pick` finds, searching from the leaf up.
+fn chain_first(
+    chain: &[ChainLink],
+    pick: impl Fn(&FilamentProfile) -> Option<String>,
+) -> Option<String> {
+    chain.iter().rev().find_map(|l| pick(&l.profile))
+}
+
+fn first_extra(profile: &FilamentProfile, key: &str) -> Option<String> {
+    match profile.extra.get(key)? {
+        Value::Array(items) => items.first()?.as_str().map(str::to_string),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// A light summary of every user profile for the library view, in one call.
+/// Chains are walked through the system index but never merged; each field is
+/// taken from the nearest profile that sets it.
+#[tauri::command]
+fn library_overview() -> Result<Vec<ProfileOverview>, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    Ok(user_filament_profiles()
+        .into_iter()
+        .map(|(name, path)| overview(name, &path, &opts))
+        .collect())
+}
+
+/// [`ProfileOverview`] of the user profile `name` stored at `path`
+fn overview(name: String, path: &Path, opts: &ResolveOptions) -> ProfileOverview {
+    let (chain, error) = match resolve_chain_from(path, opts) {
+        Ok(r) => (r.chain, None),
+        Err(e) => {
+            let own = load_profile(path, &mut Vec::new())
+                .ok()
+                .map(|profile| ChainLink {
+                    name: name.clone(),
+                    path: path.to_path_buf(),
+                    modified: None,
+                    profile,
+                });
+            (own.into_iter().collect(), Some(e.to_string()))
+        }
+    };
+    ProfileOverview {
+        filament_type: chain_first(&chain, |p| p.filament_type.as_ref()?.first().cloned()),
+        vendor: profile_vendor(&chain),
+        color: chain_first(&chain, |p| first_extra(p, "filament_colour")),
+        broken: error.is_some(),
+        error,
+        name,
+    }
+}
+
+/// User filament folders without a single profile in them, e.g. left behind
+/// by an account that is no longer used. Folders that can't be read aren't
+/// listed, since they may well hold profiles.
+#[tauri::command]
+fn list_empty_user_dirs() -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    let extensions = profile_extensions();
+    Ok(user_filament_dirs()
+        .into_iter()
+        .filter(|dir| {
+            let mut files = Vec::new();
+            let mut failed = Vec::new();
+            collect_profile_files(dir, 0, &extensions, &mut files, &mut failed);
+            files.is_empty() && failed.is_empty()
+        })
+        .map(|dir| dir.display().to_string())
+        .collect())
+}
+
+/// Every top-level key of the resolved user profiles, with how many profiles
+/// have it. Profiles that don't resolve are left out.
+fn setting_key_counts() -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, map) in resolved_user_profiles() {
+        for key in map.into_iter().map(|(k, _)| k) {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Every user filament profile that resolves, as name → resolved profile
+fn resolved_user_profiles() -> Vec<(String, serde_json::Map<String, Value>)> {
+    let opts = ResolveOptions::default();
+    user_filament_profiles()
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let resolved = resolve_chain_from(&path, &opts)
+                .map_err(String::from)
+                .and_then(|r| build_final(&r.chain, &name)?.to_value());
+            match resolved {
+                Ok(Value::Object(map)) => Some((name, map)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The range of each of `keys` across the resolved user profiles: for every
+/// key its `min`, `max` and `mean`, and how many values went into them
+/// (`count`; each extruder's entry counts). Profiles without the key are
+/// counted under `missing`, values that aren't numbers under `nonNumeric`;
+/// `min`, `max` and `mean` are null when no value was a number.
+#[tauri::command]
+fn settings_envelope(keys: Vec<String>) -> Result<Value, String> {
+    ensure_installed()?;
+    let profiles = resolved_user_profiles();
+    let mut out = serde_json::Map::new();
+    for key in keys {
+        let (mut numbers, mut missing, mut non_numeric) = (Vec::new(), 0, 0);
+        for (_, profile) in &profiles {
+            let values = match profile.get(&key) {
+                None => {
+                    missing += 1;
+                    continue;
+                }
+                Some(Value::Array(items)) => items.iter().collect(),
+                Some(v) => vec![v],
+            };
+            for v in values {
+                match normalize::as_number(v) {
+                    Some(n) => numbers.push(n),
+                    None => non_numeric += 1,
+                }
+            }
+        }
+        let min = numbers.iter().copied().reduce(f64::min);
+        let max = numbers.iter().copied().reduce(f64::max);
+        let mean =
+            (!numbers.is_empty()).then(|| numbers.iter().sum::<f64>() / numbers.len() as f64);
+        out.insert(
+            key,
+            serde_json::json!({
+                "min": min,
+                "max": max,
+                "mean": mean,
+                "count": numbers.len(),
+                "missing": missing,
+                "nonNumeric": non_numeric,
+            }),
+        );
+    }
+    Ok(serde_json::json!({ "profiles": profiles.len(), "keys": out }))
+}
+
+/// The sorted union of setting keys across the resolved user profiles.
+#[tauri::command]
+fn all_setting_keys() -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    Ok(setting_key_counts().into_keys().collect())
+}
+
+/// [`all_setting_keys`] with how many profiles use each key; a key only one
+/// or two profiles have is often a typo.
+#[tauri::command]
+fn all_setting_key_counts() -> Result<Vec<(String, usize)>, String> {
+    ensure_installed()?;
+    Ok(setting_key_counts().into_iter().collect())
+}
+
+/// How many user profiles each vendor has; profiles that don't resolve to a
+/// vendor are counted under "Unknown".
+#[tauri::command]
+fn vendor_profile_counts() -> Result<Vec<(String, usize)>, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for path in user_filament_profiles().into_values() {
+        let vendor = resolve_chain_from(&path, &opts)
+            .ok()
+            .and_then(|r| profile_vendor(&r.chain))
+            .unwrap_or_else(|| "Unknown".into());
+        *counts.entry(vendor).or_default() += 1;
+    }
+    Ok(counts.into_iter().collect())
+}
+
+/// What `value` holds under `key`, either a top-level key or a dotted path
+/// into nested objects, e.g. `"filament_settings.pressure_advance"`.
+fn value_at_path<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    if let Some(v) = value.get(key) {
+        return Some(v);
+    }
+    key.split('.').try_fold(value, |at, part| at.get(part))
+}
+
+/// User profiles whose file differs from how OrcaSlicer would save the same
+/// settings, e.g. a JSON number where it writes a string, or a boolean
+/// spelled `true` instead of `"1"`; see `normalize::normalize_profile`.
+/// Formatting and key order don't count. Nothing is written.
+#[tauri::command]
+fn list_nonconforming_profiles() -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    Ok(user_filament_profiles()
+        .into_iter()
+        .filter(|(_, path)| match load_json(path, &mut Vec::new()) {
+            Ok(Value::Object(raw)) => normalize::normalize_profile(&raw) != raw,
+            _ => false,
+        })
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// User profiles whose own file sets `key`, ignoring anything inherited.
+/// Shows where a setting is actually configured rather than what it resolves to.
+#[tauri::command]
+fn find_profiles_overriding(key: String) -> Result<Vec<String>, String> {
+    ensure_installed()?;
+    Ok(user_filament_profiles()
+        .into_iter()
+        .filter(|(_, path)| {
+            load_json(path, &mut Vec::new()).is_ok_and(|raw| value_at_path(&raw, &key).is_some())
+        })
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Compares the user library against a reference set of profiles, a folder
+/// or a zip such as a `.orca_filament` bundle. Profiles are matched by name
+/// and compared as written, before inheritance, so a team's presets diff the
+/// same way they'd be shared; the stored ignored keys (see
+/// `Settings::ignored_keys`) don't count as differences. Returns
+/// `{"onlyLocal": [..], "onlyReference": [..], "differing": {name: changes},
+/// "errors": {name: message}}` with each entry's changes as
+/// [`diff::changes`] reports them, going from the reference to the local
+/// copy. A local file that can't be read is listed under `errors` and the
+/// rest are still compared.
+#[tauri::command]
+fn compare_library(reference: String) -> Result<Value, String> {
+    ensure_installed()?;
+    let mut theirs = bundle::read_profiles(Path::new(&reference))?;
+    let ignored = Settings::load().ignored_keys();
+    let mut only_local = Vec::new();
+    let mut differing = serde_json::Map::new();
+    let mut errors = serde_json::Map::new();
+    for (name, path) in user_filament_profiles() {
+        let Some(mut reference) = theirs.remove(&name) else {
+            only_local.push(name);
+            continue;
+        };
+        let mut ours = match load_json(&path, &mut Vec::new()) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => {
+                let e = format!("{} is not a JSON object", path.display());
+                errors.insert(name, Value::String(e));
+                continue;
+            }
+            Err(e) => {
+                errors.insert(name, Value::String(e.to_string()));
+                continue;
+            }
+        };
+        reference.retain(|k, _| !ignored.contains(k));
+        ours.retain(|k, _| !ignored.contains(k));
+        let changes = diff::changes(&reference, &ours);
+        let same = ["added", "removed", "changed"]
+            .iter()
+            .all(|k| changes[k].as_object().is_some_and(|m| m.is_empty()));
+        if !same {
+            differing.insert(name, changes);
+        }
+    }
+    let only_reference: Vec<String> = theirs.into_keys().collect();
+    Ok(serde_json::json!({
+        "onlyLocal": only_local,
+        "onlyReference": only_reference,
+        "differing": differing,
+        "errors": errors,
+    }))
+}
+
+/// Resolves every profile file in `dir` against the installed bases and
+/// validates the result, without importing anything. Returns the issues for
+/// each file by file name; an empty list means the file is fine.
+#[tauri::command]
+fn validate_directory(
+    dir: String,
+    operation_id: Option<String>,
+    ops: State<'_, Cancellations>,
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let op = ops.start(operation_id);
+    let extensions = profile_extensions();
+    let read = fs::read_dir(&dir).map_err(|e| format!("read {}: {}", dir, e))?;
+    let mut files: Vec<PathBuf> = read
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && has_profile_extension(p, &extensions))
+        .collect();
+    files.sort();
+
+    let opts = ResolveOptions::default();
+    let mut out = Vec::new();
+    for path in files {
+        op.check()?;
+        let file_name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let issues = match resolve_chain_from(&path, &opts) {
+            Ok(Resolution { chain, .. }) => {
+                let name = chain
+                    .last()
+                    .map(|l| l.name.clone())
+                    .unwrap_or_else(|| file_name.clone());
+                match build_final(&chain, &name)?.to_value()? {
+                    Value::Object(map) => validate::validate_profile(&map),
+                    _ => vec!["profile is not a JSON object".to_string()],
+                }
+            }
+            Err(e) => vec![e.to_string()],
+        };
+        out.push((file_name, issues));
+    }
+    Ok(out)
+}
+
+/// The print-critical settings (temperatures, flow) that `start` ends up
+/// without once resolved, typically because a base was missing. Empty when
+/// the profile is safe to print with.
+#[tauri::command]
+fn check_essential_settings(start: String) -> Result<Vec<String>, String> {
+    let report = build_filament_profile_detailed(start.clone(), None)?;
+    let Value::Object(map) = report.profile else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+    Ok(validate::missing_essential_keys(&map)
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Severity {
+    /// OrcaSlicer will reject the profile or slice with it badly
+    Error,
+    /// Probably a mistake, but the profile works
+    Warning,
+    /// Untidy, harmless
+    Info,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreflightIssue {
+    severity: Severity,
+    message: String,
+}
+
+/// Runs every check the exporter has on `start` and returns what they found,
+/// errors first: whether the chain resolves, resolver warnings, the shape
+/// checks of `validate_profile`, essential print settings, whether its
+/// compatible printers are installed, and keys its own file sets to nothing.
+/// An empty list means the profile should slice.
+#[tauri::command]
+fn preflight_check(start: String) -> Result<Vec<PreflightIssue>, String> {
+    ensure_installed()?;
+    let issue = |severity, message: String| PreflightIssue { severity, message };
+    let Resolution { chain, warnings } = match resolve_chain(&start, &ResolveOptions::default()) {
+        Ok(r) => r,
+        Err(e) => return Ok(vec![issue(Severity::Error, e.to_string())]),
+    };
+    let mut issues: Vec<PreflightIssue> = warnings
+        .into_iter()
+        .map(|w| issue(Severity::Warning, w))
+        .collect();
+    let leaf = chain.last().ok_or("empty chain")?;
+    let name = leaf
+        .profile
+        .name
+        .clone()
+        .unwrap_or_else(|| leaf.name.clone());
+    let Value::Object(profile) = build_final(&chain, &name)?.to_value()? else {
+        return Err(format!("'{}' did not resolve to an object", start));
+    };
+
+    issues.extend(
+        validate::validate_profile(&profile)
+            .into_iter()
+            .map(|m| issue(Severity::Error, m)),
+    );
+    // a required key that's missing or empty was reported just above
+    let flagged = |k: &str| {
+        validate::REQUIRED_KEYS.contains(&k) && profile.get(k).is_none_or(validate::is_empty)
+    };
+    for key in validate::missing_essential_keys(&profile) {
+        if !flagged(key) {
+            issues.push(issue(
+                Severity::Error,
+                format!("'{}' needs a number for every extruder", key),
+            ));
+        }
+    }
+
+    let printers: Vec<String> = printer_files()
+        .iter()
+        .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    let listed = listed_printers(&profile);
+    let condition = profile
+        .get("compatible_printers_condition")
+        .and_then(Value::as_str)
+        .filter(|c| !c.trim().is_empty());
+    if printers.is_empty() {
+        issues.push(issue(
+            Severity::Info,
+            "no printer profiles installed to check compatibility against".into(),
+        ));
+    } else if !listed.is_empty() {
+        for pattern in listed {
+            if !printers.iter().any(|p| wildcard_match(pattern, p)) {
+                issues.push(issue(
+                    Severity::Warning,
+                    format!("compatible printer '{}' isn't installed", pattern),
+                ));
+            }
+        }
+    } else if let Some(condition) = condition {
+        let outcomes: Vec<Result<bool, String>> = printers
+            .iter()
+            .map(|p| condition::evaluate(condition, &resolve_printer(p)?))
+            .collect();
+        let unchecked = outcomes.iter().find_map(|o| o.as_ref().err());
+        let message = match unchecked {
+            _ if outcomes.iter().any(|o| matches!(o, Ok(true))) => None,
+            Some(e) if outcomes.iter().all(Result::is_err) => Some(format!(
+                "compatible_printers_condition can't be checked: {}",
+                e
+            )),
+            _ => Some("compatible_printers_condition matches no installed printer".to_string()),
+        };
+        issues.extend(message.map(|m| issue(Severity::Warning, m)));
+    }
+
+    if let Value::Object(own) = leaf.profile.to_value()? {
+        for (key, v) in &own {
+            if validate::is_empty(v) && !validate::REQUIRED_KEYS.contains(&key.as_str()) {
+                issues.push(issue(Severity::Info, format!("'{}' is set but empty", key)));
+            }
+        }
+    }
+
+    issues.sort_by_key(|i| i.severity);
+    Ok(issues)
+}
+
+/// The places in `name`'s chain where the declared profile type changes;
+/// empty when the chain is consistent.
+#[tauri::command]
+fn check_type_consistency(name: String) -> Result<Vec<TypeCrossing>, String> {
+    ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let chain = resolve_chain(&name, &opts)?.chain;
+    Ok(type_crossings(&chain))
+}
+
+/// What `profile_summary` shows: label, key and the unit its value is in
+const SUMMARY_KEYS: &[(&str, &str, &str)] = &[
+    ("Material", "filament_type", ""),
+    ("Vendor", "filament_vendor", ""),
+    ("Nozzle", "nozzle_temperature", " °C"),
+    (
+        "Nozzle, first layer",
+        "nozzle_temperature_initial_layer",
+        " °C",
+    ),
+    ("Bed", "hot_plate_temp", " °C"),
+    ("Bed, first layer", "hot_plate_temp_initial_layer", " °C"),
+    ("Flow ratio", "filament_flow_ratio", ""),
+    (
+        "Max volumetric speed",
+        "filament_max_volumetric_speed",
+        " mm³/s",
+    ),
+    ("Fan min", "fan_min_speed", " %"),
+    ("Fan max", "fan_max_speed", " %"),
+    (
+        "Fan off for first layers",
+        "close_fan_the_first_x_layers",
+        "",
+    ),
+];
+
+/// A short text block with the settings that matter most when picking a
+/// filament, for tooltips and sharing. Settings the profile doesn't have
+/// read "—".
+#[tauri::command]
+fn profile_summary(start: String) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let report = build_filament_profile_detailed(start, None)?;
+    let width = SUMMARY_KEYS
+        .iter()
+        .map(|(l, ..)| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", report.name);
+    for (label, key, unit) in SUMMARY_KEYS {
+        let items: Vec<&str> = match report.profile.get(*key) {
+            Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(s)) => vec![s.as_str()],
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect();
+        let value = if items.is_empty() {
+            "—".to_string()
+        } else {
+            format!("{}{}", items.join(", "), unit)
+        };
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {}",
+            format!("{label}:"),
+            value,
+            width = width + 1
+        );
+    }
+    Ok(out)
+}
+
+/// Every setting of the resolved profile, one per line, with the unit its
+/// value is in appended where the bundled units table knows it. A reading
+/// aid for sharing; OrcaSlicer can't import it.
+#[tauri::command]
+fn profile_summary_with_units(start: String) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let report = build_filament_profile_detailed(start, None)?;
+    let Value::Object(profile) = &report.profile else {
+        return Err(format!("'{}' did not resolve to an object", report.name));
+    };
+    let units = defaults::units();
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", report.name);
+    for (key, v) in profile {
+        let value = match v {
+            Value::String(s) => s.clone(),
+            Value::Array(items) if items.iter().all(Value::is_string) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            v => v.to_string(),
+        };
+        let value = value.replace('\n', "\\n");
+        match units.get(key) {
+            Some(unit) if !value.is_empty() => {
+                let _ = writeln!(out, "  {}: {} {}", key, value, unit);
+            }
+            _ => {
+                let _ = writeln!(out, "  {}: {}", key, value);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Keys `profile_as_gcode_comment` writes, in this order
+const GCODE_COMMENT_KEYS: &[&str] = &[
+    "filament_type",
+    "filament_vendor",
+    "filament_diameter",
+    "filament_density",
+    "filament_flow_ratio",
+    "filament_max_volumetric_speed",
+    "nozzle_temperature_initial_layer",
+    "nozzle_temperature",
+    "hot_plate_temp_initial_layer",
+    "hot_plate_temp",
+    "enable_pressure_advance",
+    "pressure_advance",
+    "fan_min_speed",
+    "fan_max_speed",
+    "close_fan_the_first_x_layers",
+    "filament_retraction_length",
+    "filament_retraction_speed",
+];
+
+/// The main printing settings of the resolved profile as `; key = value`
+/// lines, for prepending to G-code as a record of what was used. Lists are
+/// joined with commas the way OrcaSlicer writes its own config block; keys
+/// the profile doesn't set are left out.
+#[tauri::command]
+fn profile_as_gcode_comment(start: String) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let report = build_filament_profile_detailed(start, None)?;
+    let mut out = String::new();
+    let _ = writeln!(out, "; filament profile: {}", report.name);
+    for key in GCODE_COMMENT_KEYS {
+        let value = match report.profile.get(*key) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|i| match i {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => continue,
+            Some(other) => other.to_string(),
+        };
+        if !value.is_empty() {
+            let _ = writeln!(out, "; {} = {}", key, value);
+        }
+    }
+    Ok(out)
+}
+
+/// A single readable report of how `name` resolves, for pasting into support
+/// tickets. The home directory is shown as `~`.
+#[tauri::command]
+fn debug_dump(name: String) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let slicer = slicer::active();
+    let mut out = String::new();
+    let _ = writeln!(out, "debug dump for '{}'", name);
+    let _ = writeln!(out, "{}: {}", slicer.label(), slicer.root().display());
+    let _ = writeln!(out, "installed: {}", slicer.is_installed());
+
+    match resolve_chain(&name, &ResolveOptions::default()) {
+        Err(e) => {
+            let _ = writeln!(out, "\nresolution failed: {}", e);
+        }
+        Ok(Resolution { chain, warnings }) => {
+            let _ = writeln!(out, "\nchain (base first):");
+            for (i, link) in chain.iter().enumerate() {
+                let _ = writeln!(out, "  {}. {}  {}", i + 1, link.name, link.path.display());
+            }
+
+            let _ = writeln!(out, "\nwarnings:");
+            if warnings.is_empty() {
+                let _ = writeln!(out, "  (none)");
+            }
+            for w in &warnings {
+                let _ = writeln!(out, "  - {}", w);
+            }
+
+            if let Some(leaf) = chain.last() {
+                let raw = serde_json::to_string_pretty(&leaf.profile.to_value()?)
+                    .map_err(|e| e.to_string())?;
+                let _ = writeln!(out, "\nraw leaf:\n{}", raw);
+                let resolved = build_final(&chain, &leaf.name)?.to_value()?;
+                let resolved =
+                    serde_json::to_string_pretty(&resolved).map_err(|e| e.to_string())?;
+                let _ = writeln!(out, "\nresolved:\n{}", resolved);
+            }
+        }
+    }
+
+    Ok(match dirs_next::home_dir() {
+        Some(home) => out.replace(&home.display().to_string(), "~"),
+        None => out,
+    })
+}
+
+#[tauri::command]
+fn undo_last_operation(undo: State<'_, UndoStack>) -> Result<String, String> {
+    ensure_writable()?;
+    let label = undo.undo_last()?;
+    Ok(format!("undid {}", label))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(UndoStack::default())
+        .manage(Cancellations::default())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            is_orca_installed,
+            build_filament_profile,
+            build_filament_profiles,
+            build_filament_profile_with_overrides,
+            build_filament_profile_detailed,
+            build_filament_profile_fields,
+            compute_overrides,
+            overrides_as_json_patch,
+            diff_against_disk,
+            compare_profiles,
+            get_inheritance_chain,
+            resolution_trace,
+            get_profile_full,
+            root_ancestor,
+            relationship,
+            resolve_value,
+            reparent_profile,
+            update_to_latest_base,
+            preview_export_filename,
+            fork_system_profile,
+            export_filament_profile,
+            verify_export,
+            export_nondefault,
+            install_profile,
+            export_all_resolved,
+            export_for_printer,
+            list_profiles_for_printer,
+            list_uninstallable_profiles,
+            export_annotated,
+            list_user_filament_profiles,
+            list_user_filament_profiles_checked,
+            list_user_filament_profiles_detailed,
+            vendor_profile_counts,
+            compare_library,
+            all_setting_keys,
+            all_setting_key_counts,
+            settings_envelope,
+            list_nonconforming_profiles,
+            explain_key,
+            preflight_check,
+            list_empty_user_dirs,
+            build_filament_profile_pinned,
+            list_filament_profiles_page,
+            list_profiles_modified_since,
+            profile_as_gcode_comment,
+            clean_redundant_overrides,
+            normalize_from_fields,
+            library_overview,
+            find_profiles_overriding,
+            validate_directory,
+            check_essential_settings,
+            check_type_consistency,
+            profile_summary,
+            profile_summary_with_units,
+            debug_dump,
+            library::list_broken_profiles,
+            library::flag_outliers,
+            library::list_profiles_by_chain_depth,
+            library::reresolve_dependents,
+            drift::check_drift,
+            drift::accept_drift,
+            bundle::export_orca_filament,
+            bundle::export_library_for_git,
+            bundle::export_library_by_vendor,
+            bundle::import_bundle,
+            cancel::cancel_operation,
+            undo_last_operation,
+            settings::get_last_export_dir,
+            settings::set_last_export_dir,
+            settings::get_recent_profiles,
+            settings::add_favorite,
+            settings::remove_favorite,
+            settings::list_favorites,
+            settings::get_merge_rules,
+            settings::set_merge_rules,
+            settings::get_limit_merge,
+            settings::set_limit_merge,
+            settings::get_app_data_dir,
+            settings::clear_app_data,
+            settings::get_profile_extensions,
+            settings::set_profile_extensions,
+            settings::get_precedence_policy,
+            settings::set_precedence_policy,
+            settings::get_profile_aliases,
+            settings::set_profile_aliases,
+            settings::get_ignored_keys,
+            settings::set_ignored_keys,
+            snapshot::snapshot_profile,
+            snapshot::restore_snapshot,
+            snapshot::list_snapshots,
+            snapshot::prune_snapshots,
+            slicer::detect_slicers,
+            slicer::set_active_slicer
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_file_names_are_found() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = std::env::temp_dir().join(format!("orca-exporter-utf8-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(OsStr::from_bytes(b"Caf\xe9 PLA.json"));
+        // some filesystems (e.g. APFS) refuse names that aren't UTF-8
+        if fs::write(&path, r#"{"name": "Cafe PLA"}"#).is_err() {
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+
+        let mut files = Vec::new();
+        let extensions = ["json".to_string()];
+        collect_profile_files(&dir, 0, &extensions, &mut files, &mut Vec::new());
+        assert_eq!(files, vec![path.clone()]);
+        assert!(answers_to(&path, "Cafe PLA.json"));
+        assert!(answers_to(&path, "Caf\u{FFFD} PLA.json"));
+        assert!(!answers_to(&path, "Other.json"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn relative_inherits_resolve_inside_root_only() {
+        let tmp = std::env::temp_dir().join(format!("orca-exporter-rel-{}", std::process::id()));
+        let root = tmp.join("root");
+        let dir = root.join("system/Vendor/filament");
+        fs::create_dir_all(dir.join("base")).unwrap();
+        fs::write(dir.join("base/Base PLA.json"), "{}").unwrap();
+        fs::write(root.join("system/common.json"), "{}").unwrap();
+        fs::write(tmp.join("outside.json"), "{}").unwrap();
+
+        let expect = dir.join("base/Base PLA.json").canonicalize().unwrap();
+        assert_eq!(
+            relative_inherit(&dir, "base/Base PLA", &root),
+            Some(expect.clone())
+        );
+        assert_eq!(
+            relative_inherit(&dir, "base\\Base PLA.json", &root),
+            Some(expect)
+        );
+        assert!(relative_inherit(&dir, "../../common", &root).is_some());
+        assert_eq!(relative_inherit(&dir, "../../../../outside", &root), None);
+        assert_eq!(relative_inherit(&dir, "base/Missing", &root), None);
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn missing_type_is_stamped_from_profile_folder() {
+        let link = |dir: &str| ChainLink {
+            name: "Typeless".into(),
+            path: PathBuf::from(format!("user/123/{}/Typeless.json", dir)),
+            modified: None,
+            profile: FilamentProfile::default(),
+        };
+        for kind in ["filament", "process", "machine"] {
+            let chain = [link(kind)];
+            assert_eq!(
+                build_final(&chain, "Typeless").unwrap().kind.as_deref(),
+                Some(kind)
+            );
+            assert_eq!(build_final_as(&chain, "Typeless", None).unwrap().kind, None);
+        }
+
+        let mut typed = link("process");
+        typed.profile.kind = Some("filament".into());
+        let chain = [typed];
+        assert_eq!(
+            build_final(&chain, "Typeless").unwrap().kind.as_deref(),
+            Some("filament")
+        );
+        assert_eq!(
+            build_final_as(&chain, "Typeless", None)
+                .unwrap()
+                .kind
+                .as_deref(),
+            Some("filament")
+        );
+    }
+
+    #[test]
+    fn precedence_policy_orders_user_and_system_profiles() {
+        use settings::{Precedence, PrecedencePolicy};
+
+        let user = vec![PathBuf::from("user/1/filament/Generic PLA.json")];
+        let system = vec![PathBuf::from("system/Generic/filament/Generic PLA.json")];
+        let first = |precedence| by_precedence(user.clone(), system.clone(), precedence)[0].clone();
+
+        let mut policy = PrecedencePolicy::default();
+        assert_eq!(policy.for_name("Generic PLA"), Precedence::UserFirst);
+        assert_eq!(first(policy.for_name("Generic PLA")), user[0]);
+
+        policy.default = Precedence::SystemFirst;
+        assert_eq!(first(policy.for_name("Generic PLA")), system[0]);
+
+        policy
+            .overrides
+            .insert("Generic PLA".into(), Precedence::UserFirst);
+        assert_eq!(first(policy.for_name("Generic PLA")), user[0]);
+        assert_eq!(policy.for_name("Generic PETG"), Precedence::SystemFirst);
+    }
+
+    #[test]
+    fn value_type_changes_between_layers_are_reported() {
+        let link = |name: &str, v: Value| ChainLink {
+            name: name.into(),
+            path: PathBuf::from(format!("{}.json", name)),
+            modified: None,
+            profile: FilamentProfile::try_from(v).unwrap(),
+        };
+        let chain = [
+            link(
+                "Base",
+                serde_json::json!({"pressure_advance": "0.04", "hot_plate_temp": ["60"]}),
+            ),
+            link(
+                "Mine",
+                serde_json::json!({"pressure_advance": 0.05, "hot_plate_temp": ["65"]}),
+            ),
+        ];
+        assert_eq!(
+            value_type_changes(&chain).unwrap(),
+            vec!["'pressure_advance' is a string in 'Base' but a number in 'Mine'"]
+        );
+        assert!(value_type_changes(&chain[..1]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn empty_chain_is_an_error() {
+        let err = build_final(&[], "Nothing").expect_err("nothing to build from");
+        assert!(matches!(&err, Error::EmptyChain(name) if name == "Nothing"));
+        assert!(build_final_as(&[], "Nothing", None).is_err());
+    }
+
+    #[test]
+    fn self_inheritance_is_reported_as_such() {
+        let dir = std::env::temp_dir().join(format!("orca-exporter-self-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Loop PLA.json");
+        fs::write(&path, r#"{"name": "Loop PLA", "inherits": "Loop PLA"}"#).unwrap();
+
+        let err = resolve_chain_from(&path, &ResolveOptions::default())
+            .err()
+            .expect("a self-inheriting profile must not resolve");
+        assert!(matches!(&err, Error::InheritsItself(name) if name == "Loop PLA"));
+        assert_eq!(err.to_string(), "profile 'Loop PLA' inherits from itself");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn conflicting_types_are_found_through_untyped_layers() {
+        let link = |name: &str, kind: Option<&str>| ChainLink {
+            name: name.into(),
+            path: PathBuf::from(format!("{}.json", name)),
+            modified: None,
+            profile: FilamentProfile {
+                kind: kind.map(String::from),
+                ..Default::default()
+            },
+        };
+        let chain = [
+            link("fdm_process_common", Some("process")),
+            link("Middle", None),
+            link("My PLA", Some("filament")),
+        ];
+        let crossings = type_crossings(&chain);
+        assert_eq!(crossings.len(), 1);
+        assert_eq!(crossings[0].child, "My PLA");
+        assert_eq!(crossings[0].child_type, "filament");
+        assert_eq!(crossings[0].parent, "fdm_process_common");
+        assert_eq!(crossings[0].parent_type, "process");
+
+        let consistent = [
+            link("Base", Some("filament")),
+            link("My PLA", Some("filament")),
+        ];
+        assert!(type_crossings(&consistent).is_empty());
+    }
+
+    #[test]
+    fn crlf_exports_end_every_line_with_crlf() {
+        let profile = serde_json::json!({
+            "name": "My PLA",
+            "filament_start_gcode": ["M104 S215\nM109 S215"],
+        });
+        let pretty = to_pretty_json(&profile, None).unwrap();
+
+        let crlf = LineEnding::Crlf.apply(pretty.clone());
+        let bytes = crlf.as_bytes();
+        let breaks: Vec<usize> = (0..bytes.len()).filter(|&i| bytes[i] == b'\n').collect();
+        assert_eq!(breaks.len(), pretty.lines().count() - 1);
+        assert!(breaks.iter().all(|&i| i > 0 && bytes[i - 1] == b'\r'));
+        // the escaped newline inside the G-code string is left alone
+        assert!(crlf.contains(r"M104 S215\nM109 S215"));
+        assert_eq!(serde_json::from_str::<Value>(&crlf).unwrap(), profile);
+
+        assert_eq!(LineEnding::Lf.apply(pretty.clone()), pretty);
+        assert!(!pretty.contains('\r'));
+    }
+
+    #[test]
+    fn oversized_profile_files_are_rejected() {
+        let path =
+            std::env::temp_dir().join(format!("orca-exporter-big-{}.json", std::process::id()));
+        let file = fs::File::create(&path).unwrap();
+        file.set_len(DEFAULT_MAX_PROFILE_BYTES + 1).unwrap();
+
+        let result = load_json(&path, &mut Vec::new());
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(Error::TooLarge { size, limit, .. }) => {
+                assert_eq!(size, DEFAULT_MAX_PROFILE_BYTES + 1);
+                assert_eq!(limit, DEFAULT_MAX_PROFILE_BYTES);
+            }
+            other => panic!("expected TooLarge, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_only_mode_writes_nothing() {
+        let dir = std::env::temp_dir().join(format!("orca-exporter-ro-{}", std::process::id()));
+        let out = dir.join("export.json");
+        let undo = UndoStack::default();
+
+        let result = write_filament_profile(
+            "Any PLA".into(),
+            out.display().to_string(),
+            None,
+            ExportFormat {
+                indent: None,
+                color: None,
+                line_ending: LineEnding::Lf,
+                verify: false,
+            },
+            &undo,
+            true,
+        );
+
+        assert!(result.is_err_and(|e| e.starts_with("read-only mode")));
+        assert!(!out.exists());
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    build_filament_profiles, cancel::Cancellations, defaults, error::Error, has_profile_extension,
+    index, kind_from_path, load_profile, normalize, orca_root, profile_extensions,
+    resolve_chain_from, strip_profile_extension, user_filament_profiles, user_profile_files,
+    walk_links, BuildResult, ResolveOptions,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+use tauri::State;
+
+/// What is wrong with a profile, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    ParseError,
+    ReadError,
+    Cycle,
+    MissingAncestor,
+    TypeMismatch,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileIssue {
+    pub profile: String,
+    pub path: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+fn classify(e: &Error) -> IssueKind {
+    match e {
+        Error::Parse(_) => IssueKind::ParseError,
+        Error::Cycle(_) | Error::InheritsItself(_) => IssueKind::Cycle,
+        Error::NotFound { .. } | Error::Ambiguous { .. } => IssueKind::MissingAncestor,
+        Error::Io(_) | Error::NotInstalled(..) | Error::TooLarge { .. } => IssueKind::ReadError,
+        Error::TypeMismatch(_) => IssueKind::TypeMismatch,
+        Error::EmptyChain(_) => IssueKind::MissingAncestor,
+    }
+}
+
+/// User profiles whose chain doesn't resolve, parse errors first.
+/// Cancellable through `operation_id`.
+#[tauri::command]
+pub fn list_broken_profiles(
+    operation_id: Option<String>,
+    ops: State<'_, Cancellations>,
+) -> Result<Vec<ProfileIssue>, String> {
+    crate::ensure_installed()?;
+    let op = ops.start(operation_id);
+    let opts = ResolveOptions::default();
+    let mut issues = Vec::new();
+    for (name, path) in user_filament_profiles() {
+        op.check()?;
+        if let Err(e) = resolve_chain_from(&path, &opts) {
+            issues.push(ProfileIssue {
+                profile: name,
+                path: path.display().to_string(),
+                kind: classify(&e),
+                message: e.to_string(),
+            });
+        }
+    }
+    // stable: keeps name order within each kind
+    issues.sort_by_key(|i| i.kind);
+    Ok(issues)
+}
+
+/// A resolved value outside the range expected for its material
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Outlier {
+    pub profile: String,
+    pub key: String,
+    /// The offending value, one extruder's for a list
+    pub value: serde_json::Value,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Resolved user profiles' values that fall outside the range table for
+/// their `filament_type`; see `defaults::ranges`. Materials the table
+/// doesn't know get just the `*` ranges, and values that aren't numbers are
+/// left alone.
+#[tauri::command]
+pub fn flag_outliers() -> Result<Vec<Outlier>, String> {
+    crate::ensure_installed()?;
+    let table = defaults::ranges()?;
+    let mut outliers = Vec::new();
+    for (name, profile) in crate::resolved_user_profiles() {
+        let material = match profile.get("filament_type") {
+            Some(serde_json::Value::Array(items)) => items.first().and_then(|v| v.as_str()),
+            Some(v) => v.as_str(),
+            None => None,
+        };
+        let mut ranges = table.get("*").cloned().unwrap_or_default();
+        if let Some((_, own)) =
+            material.and_then(|m| table.iter().find(|(k, _)| k.eq_ignore_ascii_case(m)))
+        {
+            ranges.extend(own.clone());
+        }
+        for (key, (min, max)) in ranges {
+            let values = match profile.get(&key) {
+                Some(serde_json::Value::Array(items)) => items.iter().collect(),
+                Some(v) => vec![v],
+                None => Vec::new(),
+            };
+            for v in values {
+                if normalize::as_number(v).is_some_and(|n| n < min || n > max) {
+                    outliers.push(Outlier {
+                        profile: name.clone(),
+                        key: key.clone(),
+                        value: v.clone(),
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+    }
+    Ok(outliers)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainDepth {
+    pub profile: String,
+    /// Layers in the chain, the profile itself included
+    pub depth: usize,
+    /// The chain doesn't resolve; `depth` counts the layers up to the break
+    pub broken: bool,
+}
+
+/// Every user profile with the length of its inheritance chain, deepest first.
+#[tauri::command]
+pub fn list_profiles_by_chain_depth() -> Result<Vec<ChainDepth>, String> {
+    crate::ensure_installed()?;
+    let opts = ResolveOptions::default();
+    let mut depths: Vec<ChainDepth> = user_filament_profiles()
+        .into_iter()
+        .map(|(name, path)| {
+            let mut chain = Vec::new();
+            let result = walk_links(&name, Some(path), &opts, &mut chain, &mut Vec::new());
+            ChainDepth {
+                profile: name,
+                depth: chain.len(),
+                broken: result.is_err(),
+            }
+        })
+        .collect();
+    // stable: keeps name order among equal depths
+    depths.sort_by_key(|d| std::cmp::Reverse(d.depth));
+    Ok(depths)
+}
+
+/// The file name part of a reference, which is what `inherits` points at
+fn reference_stem<'a>(reference: &'a str, extensions: &[String]) -> &'a str {
+    let last = reference.rsplit(['/', '\\']).next().unwrap_or(reference);
+    strip_profile_extension(last, extensions).unwrap_or(last)
+}
+
+/// Which profiles directly inherit from each profile, by file name. Only the
+/// `inherits` key of each filament profile is read, which is far cheaper
+/// than resolving every chain.
+fn reverse_dependencies(extensions: &[String]) -> BTreeMap<String, BTreeSet<String>> {
+    let (system, _) = index::system_index(
+        &orca_root().join("system"),
+        ResolveOptions::default().search_timeout(),
+    );
+    let files: Vec<PathBuf> = system
+        .paths()
+        .filter(|p| kind_from_path(p) == Some("filament") && has_profile_extension(p, extensions))
+        .cloned()
+        .chain(user_profile_files())
+        .collect();
+    let mut children: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for path in files {
+        let Some(stem) = path.file_stem() else {
+            continue;
+        };
+        if let Ok(profile) = load_profile(&path, &mut Vec::new()) {
+            if let Some(parent) = profile.inherits.as_deref() {
+                children
+                    .entry(reference_stem(parent, extensions).to_string())
+                    .or_default()
+                    .insert(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    children
+}
+
+/// Re-resolves only the user profiles that inherit, directly or through
+/// other bases, from `changed`, for watch mode to call when that one file
+/// was saved. Returns the fresh results in name order; the profile itself is
+/// included when it is a user profile. Cancellable through `operation_id`.
+#[tauri::command]
+pub fn reresolve_dependents(
+    changed: String,
+    operation_id: Option<String>,
+    ops: State<'_, Cancellations>,
+) -> Result<Vec<BuildResult>, String> {
+    crate::ensure_installed()?;
+    let extensions = profile_extensions();
+    let children = reverse_dependencies(&extensions);
+    let mut affected = BTreeSet::from([reference_stem(&changed, &extensions).to_string()]);
+    let mut queue: Vec<String> = affected.iter().cloned().collect();
+    while let Some(name) = queue.pop() {
+        for child in children.get(&name).into_iter().flatten() {
+            if affected.insert(child.clone()) {
+                queue.push(child.clone());
+            }
+        }
+    }
+
+    let stem_of = |p: &Path| p.file_stem().map(|s| s.to_string_lossy().into_owned());
+    let names = user_filament_profiles()
+        .into_iter()
+        .filter(|(name, path)| {
+            affected.contains(name) || stem_of(path).is_some_and(|s| affected.contains(&s))
+        })
+        .map(|(name, _)| name)
+        .collect();
+    build_filament_profiles(names, operation_id, ops)
+}
@@ -0,0 +1,171 @@
+use crate::validate::STRING_KEYS;
+use serde_json::{Map, Value};
+
+/// Filament keys holding a boolean, which OrcaSlicer writes as `"0"`/`"1"`
+/// (inside a list, like every other per-extruder value).
+pub const NUMERIC_BOOL_KEYS: &[&str] = &[
+    "activate_air_filtration",
+    "activate_chamber_temp_control",
+    "dont_slow_down_outer_wall",
+    "enable_overhang_bridge_fan",
+    "enable_pressure_advance",
+    "filament_is_support",
+    "filament_soluble",
+    "filament_wipe",
+    "reduce_fan_stop_start_freq",
+    "slow_down_for_layer_cooling",
+];
+
+/// Keys holding a boolean written as `"true"`/`"false"`.
+pub const WORD_BOOL_KEYS: &[&str] = &["instantiation"];
+
+/// Reads OrcaSlicer's spellings of a boolean: `"0"`/`"1"`, `"true"`/`"false"`
+/// and plain JSON booleans, which turn up in hand-written profiles.
+pub fn as_bool(v: &Value) -> Option<bool> {
+    match v {
+        Value::Bool(b) => Some(*b),
+        Value::String(s) => match s.as_str() {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Reads a number OrcaSlicer wrote as a string, e.g. `"220"` or `"0.98"`.
+/// A decimal comma, as some localized exports have (`"0,98"`), reads too;
+/// text that could be either a decimal or a thousands separator doesn't.
+pub fn as_number(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => match read_number(s) {
+            NumberText::Canonical(n) | NumberText::DecimalComma(n) => Some(n),
+            NumberText::Ambiguous | NumberText::NotANumber => None,
+        },
+        _ => None,
+    }
+}
+
+/// How a string reads as a number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberText {
+    /// `"0.98"`, as OrcaSlicer writes numbers
+    Canonical(f64),
+    /// `"0,98"`: one comma between digits, not followed by exactly three
+    DecimalComma(f64),
+    /// `"1,250"`, `"1,234.5"`, `"1.234,5"`: the comma may separate thousands
+    Ambiguous,
+    NotANumber,
+}
+
+pub fn read_number(s: &str) -> NumberText {
+    let s = s.trim();
+    if let Ok(n) = s.parse() {
+        return NumberText::Canonical(n);
+    }
+    let digits = |t: &str| !t.is_empty() && t.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let Some((int, frac)) = unsigned.split_once(',') else {
+        return NumberText::NotANumber;
+    };
+    let int_ok = digits(int) || (int.contains('.') && int.split('.').all(digits));
+    let frac_ok = digits(frac) || frac.split([',', '.']).all(digits);
+    if !int_ok || !frac_ok {
+        NumberText::NotANumber
+    } else if digits(int) && digits(frac) && frac.len() != 3 {
+        match s.replacen(',', ".", 1).parse() {
+            Ok(n) => NumberText::DecimalComma(n),
+            Err(_) => NumberText::NotANumber,
+        }
+    } else {
+        NumberText::Ambiguous
+    }
+}
+
+/// Rewrites decimal-comma numbers in `profile` with a decimal point, leaving
+/// the digits as they were. Returns the keys holding numbers whose comma
+/// might separate thousands; those are left untouched.
+pub fn canonicalize_numbers(profile: &mut Map<String, Value>) -> Vec<String> {
+    let mut ambiguous = Vec::new();
+    for (key, v) in profile.iter_mut() {
+        if STRING_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let items = match v {
+            Value::Array(items) => items.as_mut_slice(),
+            v => std::slice::from_mut(v),
+        };
+        for item in items {
+            let Value::String(s) = item else {
+                continue;
+            };
+            match read_number(s) {
+                NumberText::DecimalComma(_) => *s = s.trim().replacen(',', ".", 1),
+                NumberText::Ambiguous if !ambiguous.contains(key) => ambiguous.push(key.clone()),
+                _ => {}
+            }
+        }
+    }
+    ambiguous
+}
+
+pub fn is_bool_key(key: &str) -> bool {
+    NUMERIC_BOOL_KEYS.contains(&key) || WORD_BOOL_KEYS.contains(&key)
+}
+
+/// Equality that doesn't tell boolean spellings apart, so `"1"`, `"true"`
+/// and `true` compare equal. That applies to the keys listed above, and to
+/// any value compared against a JSON boolean, which can only mean a boolean.
+/// Lists are compared item by item; everything else must match exactly.
+pub fn same_value(key: &str, a: &Value, b: &Value) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a, b) {
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| same_value(key, a, b))
+        }
+        (Value::Bool(_), _) | (_, Value::Bool(_)) => {
+            as_bool(a).is_some() && as_bool(a) == as_bool(b)
+        }
+        _ if is_bool_key(key) => as_bool(a).is_some() && as_bool(a) == as_bool(b),
+        _ => false,
+    }
+}
+
+/// `v` as one string OrcaSlicer would write for `key`: booleans in the
+/// key's spelling, numbers as their decimal text.
+fn to_text(key: &str, v: &Value) -> Value {
+    if let Some(b) = as_bool(v).filter(|_| is_bool_key(key) || v.is_boolean()) {
+        let spelled = if WORD_BOOL_KEYS.contains(&key) {
+            b.to_string()
+        } else {
+            u8::from(b).to_string()
+        };
+        return Value::String(spelled);
+    }
+    match v {
+        Value::Number(n) => Value::String(n.to_string()),
+        v => v.clone(),
+    }
+}
+
+/// The profile as OrcaSlicer would save it: metadata keys as plain strings,
+/// every other setting as a list of strings, with booleans and numbers
+/// spelled the way it writes them. Nested objects and nulls, which it never
+/// writes, are left alone.
+pub fn normalize_profile(profile: &Map<String, Value>) -> Map<String, Value> {
+    profile
+        .iter()
+        .map(|(k, v)| {
+            let v = match v {
+                Value::Object(_) | Value::Null => v.clone(),
+                v if STRING_KEYS.contains(&k.as_str()) => to_text(k, v),
+                Value::Array(items) => Value::Array(items.iter().map(|i| to_text(k, i)).collect()),
+                v => Value::Array(vec![to_text(k, v)]),
+            };
+            (k.clone(), v)
+        })
+        .collect()
+}
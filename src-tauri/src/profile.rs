@@ -0,0 +1,353 @@
+use crate::normalize::as_number;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A filament profile as OrcaSlicer stores it on disk.
+///
+/// Only the keys the exporter reasons about are typed; everything else is kept
+/// verbatim in `extra`, and so is a typed key whose value doesn't have the
+/// expected type, e.g. `"instantiation": true`. A list key written as a bare
+/// string is written back as one, so loading and re-serializing a profile is
+/// lossless except for typed keys set to `null`, which are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Map<String, Value>", into = "Map<String, Value>")]
+pub struct FilamentProfile {
+    pub name: Option<String>,
+    pub inherits: Option<String>,
+    pub from: Option<String>,
+    pub kind: Option<String>,
+    pub instantiation: Option<String>,
+    pub setting_id: Option<String>,
+    pub filament_id: Option<String>,
+    pub filament_type: Option<Vec<String>>,
+    pub filament_vendor: Option<Vec<String>>,
+    pub extra: Map<String, Value>,
+    /// List keys above that were written as a bare string rather than an
+    /// array; hand-edited profiles sometimes do
+    pub bare_lists: BTreeSet<String>,
+}
+
+/// Keys with a field of their own in [`FilamentProfile`]
+const TYPED_KEYS: &[&str] = &[
+    "name",
+    "inherits",
+    "from",
+    "type",
+    "instantiation",
+    "setting_id",
+    "filament_id",
+    "filament_type",
+    "filament_vendor",
+];
+
+impl From<Map<String, Value>> for FilamentProfile {
+    fn from(mut map: Map<String, Value>) -> Self {
+        let mut bare_lists = BTreeSet::new();
+        let mut string = |key: &str| match map.remove(key) {
+            Some(Value::String(s)) => Some(s),
+            Some(Value::Null) | None => None,
+            Some(other) => {
+                map.insert(key.to_string(), other);
+                None
+            }
+        };
+        let (name, inherits, from, kind) = (
+            string("name"),
+            string("inherits"),
+            string("from"),
+            string("type"),
+        );
+        let (instantiation, setting_id, filament_id) = (
+            string("instantiation"),
+            string("setting_id"),
+            string("filament_id"),
+        );
+        let mut list = |key: &str| match map.remove(key) {
+            Some(Value::String(s)) => {
+                bare_lists.insert(key.to_string());
+                Some(vec![s])
+            }
+            Some(Value::Array(items)) if items.iter().all(Value::is_string) => Some(
+                items
+                    .into_iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect(),
+            ),
+            Some(Value::Null) | None => None,
+            Some(other) => {
+                map.insert(key.to_string(), other);
+                None
+            }
+        };
+        let (filament_type, filament_vendor) = (list("filament_type"), list("filament_vendor"));
+        FilamentProfile {
+            name,
+            inherits,
+            from,
+            kind,
+            instantiation,
+            setting_id,
+            filament_id,
+            filament_type,
+            filament_vendor,
+            extra: map,
+            bare_lists,
+        }
+    }
+}
+
+impl From<FilamentProfile> for Map<String, Value> {
+    fn from(p: FilamentProfile) -> Self {
+        let mut map = p.extra;
+        let strings = [
+            ("name", p.name),
+            ("inherits", p.inherits),
+            ("from", p.from),
+            ("type", p.kind),
+            ("instantiation", p.instantiation),
+            ("setting_id", p.setting_id),
+            ("filament_id", p.filament_id),
+        ];
+        for (key, value) in strings {
+            if let Some(s) = value {
+                map.insert(key.into(), Value::String(s));
+            }
+        }
+        for (key, value) in [
+            ("filament_type", p.filament_type),
+            ("filament_vendor", p.filament_vendor),
+        ] {
+            let value = match value {
+                Some(mut items) if items.len() == 1 && p.bare_lists.contains(key) => {
+                    Value::String(items.remove(0))
+                }
+                Some(items) => Value::Array(items.into_iter().map(Value::String).collect()),
+                None => continue,
+            };
+            map.insert(key.into(), value);
+        }
+        map
+    }
+}
+
+/// How a child's value for a key combines with its parent's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// The child's value replaces the parent's outright, objects included
+    Override,
+    /// Objects are merged key by key, anything else is replaced
+    #[default]
+    Deep,
+    /// Lists keep the parent's items and add the child's that aren't there yet
+    Union,
+    /// Lists are the parent's items followed by the child's
+    Concat,
+    /// The smaller number wins, per extruder for lists, so an upper limit set
+    /// anywhere in the chain holds
+    Min,
+    /// The larger number wins, per extruder for lists
+    Max,
+}
+
+/// Per-key strategies; keys without a rule merge as [`MergeStrategy::Deep`].
+pub type MergeRules = BTreeMap<String, MergeStrategy>;
+
+/// Keys that are hardware limits, and the strategy that keeps the tightest
+/// value in the chain: speeds, accelerations and jerks are capped from above
+/// (`min`), the nozzle temperature range shrinks from both ends. Applied
+/// when `Settings::limit_merge` is on; an explicit merge rule for a key
+/// takes precedence.
+pub const LIMIT_RULES: &[(&str, MergeStrategy)] = &[
+    ("filament_max_volumetric_speed", MergeStrategy::Min),
+    ("machine_max_acceleration_e", MergeStrategy::Min),
+    ("machine_max_acceleration_extruding", MergeStrategy::Min),
+    ("machine_max_acceleration_retracting", MergeStrategy::Min),
+    ("machine_max_acceleration_travel", MergeStrategy::Min),
+    ("machine_max_acceleration_x", MergeStrategy::Min),
+    ("machine_max_acceleration_y", MergeStrategy::Min),
+    ("machine_max_acceleration_z", MergeStrategy::Min),
+    ("machine_max_jerk_e", MergeStrategy::Min),
+    ("machine_max_jerk_x", MergeStrategy::Min),
+    ("machine_max_jerk_y", MergeStrategy::Min),
+    ("machine_max_jerk_z", MergeStrategy::Min),
+    ("machine_max_speed_e", MergeStrategy::Min),
+    ("machine_max_speed_x", MergeStrategy::Min),
+    ("machine_max_speed_y", MergeStrategy::Min),
+    ("machine_max_speed_z", MergeStrategy::Min),
+    ("nozzle_temperature_range_high", MergeStrategy::Min),
+    ("nozzle_temperature_range_low", MergeStrategy::Max),
+];
+
+impl FilamentProfile {
+    /// Layers `child` on top of `self`: every key the child sets wins, nested
+    /// objects are merged key by key unless `rules` says otherwise. Rules only
+    /// apply to the untyped keys in `extra`; the typed metadata fields always
+    /// take the child's value.
+    pub fn merge_with_rules(&mut self, child: &FilamentProfile, rules: &MergeRules) {
+        fn take<T: Clone>(into: &mut Option<T>, from: &Option<T>) {
+            if from.is_some() {
+                into.clone_from(from);
+            }
+        }
+        // a typed key the child gave an unexpected type sits in its `extra`,
+        // and still has to win over the parent's typed value, and vice versa
+        for key in child.typed_keys() {
+            self.extra.remove(key);
+        }
+        for key in TYPED_KEYS.iter().filter(|k| child.extra.contains_key(**k)) {
+            self.clear_typed(key);
+        }
+        take(&mut self.name, &child.name);
+        take(&mut self.inherits, &child.inherits);
+        take(&mut self.from, &child.from);
+        take(&mut self.kind, &child.kind);
+        take(&mut self.instantiation, &child.instantiation);
+        take(&mut self.setting_id, &child.setting_id);
+        take(&mut self.filament_id, &child.filament_id);
+        for (key, from) in [
+            ("filament_type", &child.filament_type),
+            ("filament_vendor", &child.filament_vendor),
+        ] {
+            if from.is_some() && !child.bare_lists.contains(key) {
+                self.bare_lists.remove(key);
+            } else if from.is_some() {
+                self.bare_lists.insert(key.to_string());
+            }
+        }
+        take(&mut self.filament_type, &child.filament_type);
+        take(&mut self.filament_vendor, &child.filament_vendor);
+        for (k, v) in &child.extra {
+            let into = self.extra.entry(k.clone()).or_insert(Value::Null);
+            merge_value(into, v, rules.get(k).copied().unwrap_or_default());
+        }
+    }
+
+    /// The typed keys this profile sets
+    fn typed_keys(&self) -> impl Iterator<Item = &'static str> {
+        [
+            ("name", self.name.is_some()),
+            ("inherits", self.inherits.is_some()),
+            ("from", self.from.is_some()),
+            ("type", self.kind.is_some()),
+            ("instantiation", self.instantiation.is_some()),
+            ("setting_id", self.setting_id.is_some()),
+            ("filament_id", self.filament_id.is_some()),
+            ("filament_type", self.filament_type.is_some()),
+            ("filament_vendor", self.filament_vendor.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(key, set)| set.then_some(key))
+    }
+
+    fn clear_typed(&mut self, key: &str) {
+        match key {
+            "name" => self.name = None,
+            "inherits" => self.inherits = None,
+            "from" => self.from = None,
+            "type" => self.kind = None,
+            "instantiation" => self.instantiation = None,
+            "setting_id" => self.setting_id = None,
+            "filament_id" => self.filament_id = None,
+            "filament_type" => self.filament_type = None,
+            "filament_vendor" => self.filament_vendor = None,
+            _ => {}
+        }
+    }
+
+    /// Serializes into a plain JSON object. Keys come out sorted, which keeps
+    /// exports stable regardless of which fields are typed.
+    pub fn to_value(&self) -> Result<Value, String> {
+        serde_json::to_value(self).map_err(|e| e.to_string())
+    }
+}
+
+impl TryFrom<Value> for FilamentProfile {
+    type Error = String;
+
+    fn try_from(v: Value) -> Result<Self, String> {
+        serde_json::from_value(v).map_err(|e| e.to_string())
+    }
+}
+
+/// Combines `from` into `into` as `strategy` says. Union and concat only
+/// apply when both sides are lists; otherwise they fall back to a deep merge.
+/// Min and max need numbers, or lists of them as long as each other, on both
+/// sides; otherwise the child's value wins.
+pub fn merge_value(into: &mut Value, from: &Value, strategy: MergeStrategy) {
+    match (strategy, &mut *into, from) {
+        (MergeStrategy::Min | MergeStrategy::Max, _, _) => {
+            *into = tightest(into, from, strategy == MergeStrategy::Min)
+                .unwrap_or_else(|| from.clone());
+        }
+        (MergeStrategy::Override, _, _) => *into = from.clone(),
+        (MergeStrategy::Union, Value::Array(a), Value::Array(b)) => {
+            for item in b {
+                if !a.contains(item) {
+                    a.push(item.clone());
+                }
+            }
+        }
+        (MergeStrategy::Concat, Value::Array(a), Value::Array(b)) => a.extend(b.iter().cloned()),
+        _ => deep_merge(into, from),
+    }
+}
+
+/// The smaller (`min`) or larger of two numbers, or of two equally long lists
+/// of them item by item, keeping the winner's spelling; ties go to `child`.
+fn tightest(parent: &Value, child: &Value, min: bool) -> Option<Value> {
+    let pick = |a: &Value, b: &Value| {
+        let (x, y) = (as_number(a)?, as_number(b)?);
+        let parent_wins = if min { x < y } else { x > y };
+        Some(if parent_wins { a.clone() } else { b.clone() })
+    };
+    match (parent, child) {
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| pick(x, y))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        (Value::Array(_), _) | (_, Value::Array(_)) => None,
+        (a, b) => pick(a, b),
+    }
+}
+
+pub fn deep_merge(into: &mut Value, from: &Value) {
+    if let (Some(a), Some(b)) = (into.as_object_mut(), from.as_object()) {
+        for (k, v) in b {
+            deep_merge(a.entry(k.clone()).or_insert(Value::Null), v);
+        }
+    } else {
+        *into = from.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_values_the_fields_dont_fit() {
+        let raw = json!({
+            "name": "Odd PLA",
+            "inherits": "Generic PLA",
+            "instantiation": true,
+            "filament_id": 42,
+            "filament_type": "PLA",
+            "filament_vendor": ["Acme"],
+            "nozzle_temperature": ["215"],
+        });
+        let profile = FilamentProfile::try_from(raw.clone()).unwrap();
+        assert_eq!(profile.instantiation, None);
+        assert_eq!(profile.filament_type, Some(vec!["PLA".to_string()]));
+        assert_eq!(profile.to_value().unwrap(), raw);
+
+        // the child's value wins even when only one side is typed
+        let mut parent = FilamentProfile::try_from(json!({"instantiation": "false"})).unwrap();
+        parent.merge_with_rules(&profile, &MergeRules::new());
+        assert_eq!(parent.to_value().unwrap()["instantiation"], json!(true));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{
+    profile::{MergeRules, LIMIT_RULES},
+    slicer::Slicer,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Must match `identifier` in tauri.conf.json so we share Tauri's config dir.
+const APP_IDENTIFIER: &str = "com.lucio.orca-exporter";
+
+/// Keys that only say which profile this is, left out when profiles are
+/// compared unless `Settings::ignored_keys` is set
+pub const DEFAULT_IGNORED_KEYS: &[&str] = &[
+    "filament_settings_id",
+    "from",
+    "instantiation",
+    "name",
+    "setting_id",
+];
+
+/// How many entries the recent-profiles history keeps.
+const MAX_RECENT: usize = 20;
+
+/// Serializes read-modify-write cycles on the settings file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+pub fn app_config_dir() -> PathBuf {
+    dirs_next::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_IDENTIFIER)
+}
+
+fn settings_path() -> PathBuf {
+    app_config_dir().join("settings.json")
+}
+
+/// Exporter state persisted across sessions.
+///
+/// Every field has a default so a missing or older settings file still loads.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub last_export_dir: Option<String>,
+    /// Most recent first, one entry per profile name.
+    pub recent: Vec<RecentProfile>,
+    pub favorites: BTreeSet<String>,
+    /// Chosen slicer install; auto-detected while unset.
+    pub slicer: Option<Slicer>,
+    /// Per-key merge strategies used when resolving chains.
+    pub merge_rules: MergeRules,
+    /// Keep the tightest value of each hardware limit in the chain rather
+    /// than the child's; see `profile::LIMIT_RULES`
+    pub limit_merge: bool,
+    /// File extensions profiles are recognized by, without the dot; `""`
+    /// matches files without one. Empty means the default, `json` only.
+    pub profile_extensions: Vec<String>,
+    /// Whether user or system profiles win when both answer to a name.
+    pub precedence: PrecedencePolicy,
+    /// Old profile name → new one, for `inherits` that still name a profile
+    /// a vendor has since renamed
+    pub aliases: BTreeMap<String, String>,
+    /// Keys comparisons leave out; `DEFAULT_IGNORED_KEYS` while unset
+    pub ignored_keys: Option<BTreeSet<String>>,
+}
+
+/// Which side wins when a user and a system profile answer to the same name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Precedence {
+    /// User profiles shadow system ones, as in OrcaSlicer
+    #[default]
+    UserFirst,
+    /// The system profile is used even when a user profile has its name
+    SystemFirst,
+}
+
+/// How profile names resolve when user and system profiles collide: one
+/// rule for everything, plus exceptions by name (as written in `inherits`,
+/// without the extension) that take priority over it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrecedencePolicy {
+    pub default: Precedence,
+    pub overrides: BTreeMap<String, Precedence>,
+}
+
+impl PrecedencePolicy {
+    pub fn for_name(&self, name: &str) -> Precedence {
+        self.overrides.get(name).copied().unwrap_or(self.default)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentProfile {
+    pub name: String,
+    /// Unix timestamp (seconds) of the last export.
+    pub timestamp: u64,
+}
+
+impl Settings {
+    /// `profile_extensions`, with the default filled in
+    pub fn profile_extensions(&self) -> Vec<String> {
+        if self.profile_extensions.is_empty() {
+            vec!["json".to_string()]
+        } else {
+            self.profile_extensions.clone()
+        }
+    }
+
+    /// The merge rules resolution applies: `merge_rules`, on top of the limit
+    /// rules when `limit_merge` is on
+    pub fn effective_merge_rules(&self) -> MergeRules {
+        let mut rules = MergeRules::new();
+        if self.limit_merge {
+            rules.extend(LIMIT_RULES.iter().map(|(k, s)| (k.to_string(), *s)));
+        }
+        rules.extend(self.merge_rules.clone());
+        rules
+    }
+
+    /// The keys comparisons leave out, with the default filled in
+    pub fn ignored_keys(&self) -> BTreeSet<String> {
+        match &self.ignored_keys {
+            Some(keys) => keys.clone(),
+            None => DEFAULT_IGNORED_KEYS.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    /// Loads the settings file, falling back to defaults on first run or if
+    /// the file is unreadable.
+    pub fn load() -> Settings {
+        fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = settings_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        }
+        let s = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, s.as_bytes()).map_err(|e| format!("write {}: {}", path.display(), e))
+    }
+
+    /// Moves `name` to the top of the recent history, adding it if needed.
+    pub fn push_recent(&mut self, name: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.recent.retain(|r| r.name != name);
+        self.recent.insert(
+            0,
+            RecentProfile {
+                name: name.to_string(),
+                timestamp,
+            },
+        );
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// Applies `f` to the stored settings and writes them back.
+    pub fn update(f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+        crate::ensure_writable()?;
+        let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+        let mut settings = Settings::load();
+        f(&mut settings);
+        settings.save()
+    }
+}
+
+/// Last directory an export was written to, if it still exists.
+#[tauri::command]
+pub fn get_last_export_dir() -> Result<Option<String>, String> {
+    Ok(Settings::load()
+        .last_export_dir
+        .filter(|d| PathBuf::from(d).is_dir()))
+}
+
+#[tauri::command]
+pub fn set_last_export_dir(path: String) -> Result<(), String> {
+    if !PathBuf::from(&path).is_dir() {
+        return Err(format!("not a directory: {}", path));
+    }
+    Settings::update(|s| s.last_export_dir = Some(path))
+}
+
+#[tauri::command]
+pub fn get_recent_profiles(limit: usize) -> Result<Vec<RecentProfile>, String> {
+    let mut recent = Settings::load().recent;
+    recent.truncate(limit);
+    Ok(recent)
+}
+
+#[tauri::command]
+pub fn add_favorite(name: String) -> Result<(), String> {
+    Settings::update(|s| {
+        s.favorites.insert(name);
+    })
+}
+
+#[tauri::command]
+pub fn remove_favorite(name: String) -> Result<(), String> {
+    Settings::update(|s| {
+        s.favorites.remove(&name);
+    })
+}
+
+/// Favorites that still name an existing user profile. Stale entries are
+/// pruned from the store, unless no user profiles are visible at all (e.g.
+/// the OrcaSlicer folder is temporarily unavailable) or in read-only mode.
+#[tauri::command]
+pub fn list_favorites() -> Result<Vec<String>, String> {
+    let favorites = Settings::load().favorites;
+    let existing = crate::user_filament_profiles();
+    if existing.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (live, stale): (Vec<String>, Vec<String>) = favorites
+        .into_iter()
+        .partition(|name| existing.contains_key(name));
+    if !stale.is_empty() && !crate::read_only() {
+        Settings::update(|s| s.favorites.retain(|name| !stale.contains(name)))?;
+    }
+    Ok(live)
+}
+
+#[tauri::command]
+pub fn get_merge_rules() -> Result<MergeRules, String> {
+    Ok(Settings::load().merge_rules)
+}
+
+/// Replaces the stored merge rules; an empty map restores the default merge.
+#[tauri::command]
+pub fn set_merge_rules(rules: MergeRules) -> Result<(), String> {
+    Settings::update(|s| s.merge_rules = rules)
+}
+
+#[tauri::command]
+pub fn get_limit_merge() -> Result<bool, String> {
+    Ok(Settings::load().limit_merge)
+}
+
+/// Turns the limit merge on or off; see `profile::LIMIT_RULES`.
+#[tauri::command]
+pub fn set_limit_merge(enabled: bool) -> Result<(), String> {
+    Settings::update(|s| s.limit_merge = enabled)
+}
+
+/// Where the exporter keeps its settings, baselines and index cache.
+#[tauri::command]
+pub fn get_app_data_dir() -> Result<String, String> {
+    Ok(app_config_dir().display().to_string())
+}
+
+/// Deletes everything in the app data directory, resetting the exporter to a
+/// fresh install. OrcaSlicer's own folders are never touched; a data
+/// directory that overlaps them is refused outright.
+#[tauri::command]
+pub fn clear_app_data() -> Result<(), String> {
+    crate::ensure_writable()?;
+    let dir = app_config_dir();
+    let slicer_root = crate::orca_root();
+    if slicer_root.starts_with(&dir) || dir.starts_with(&slicer_root) {
+        return Err(format!(
+            "refusing to clear {}: it overlaps the slicer folder {}",
+            dir.display(),
+            slicer_root.display()
+        ));
+    }
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    match fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("remove {}: {}", dir.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_profile_extensions() -> Result<Vec<String>, String> {
+    Ok(Settings::load().profile_extensions())
+}
+
+/// Sets which file extensions are scanned for profiles, e.g. `["json",
+/// "info"]`; a leading dot and case are ignored, and so are duplicates. The
+/// files must still hold JSON.
+#[tauri::command]
+pub fn set_profile_extensions(extensions: Vec<String>) -> Result<(), String> {
+    let extensions: BTreeSet<String> = extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect();
+    Settings::update(|s| s.profile_extensions = extensions.into_iter().collect())
+}
+
+#[tauri::command]
+pub fn get_precedence_policy() -> Result<PrecedencePolicy, String> {
+    Ok(Settings::load().precedence)
+}
+
+/// Replaces the stored precedence policy; the default policy, user first
+/// with no exceptions, restores OrcaSlicer's behavior.
+#[tauri::command]
+pub fn set_precedence_policy(policy: PrecedencePolicy) -> Result<(), String> {
+    Settings::update(|s| s.precedence = policy)
+}
+
+#[tauri::command]
+pub fn get_profile_aliases() -> Result<BTreeMap<String, String>, String> {
+    Ok(Settings::load().aliases)
+}
+
+/// Replaces the stored alias table. Names are as written in `inherits`,
+/// without the extension; an alias only applies when nothing answers to the
+/// old name itself, and isn't followed further.
+#[tauri::command]
+pub fn set_profile_aliases(aliases: BTreeMap<String, String>) -> Result<(), String> {
+    if let Some((old, _)) = aliases.iter().find(|(old, new)| old == new) {
+        return Err(format!("'{}' can't be an alias of itself", old));
+    }
+    Settings::update(|s| s.aliases = aliases)
+}
+
+#[tauri::command]
+pub fn get_ignored_keys() -> Result<BTreeSet<String>, String> {
+    Ok(Settings::load().ignored_keys())
+}
+
+/// Replaces the keys that library comparison and drift checks leave out;
+/// `None` restores `DEFAULT_IGNORED_KEYS`, an empty list compares every key.
+#[tauri::command]
+pub fn set_ignored_keys(keys: Option<Vec<String>>) -> Result<(), String> {
+    let keys = keys.map(|k| k.into_iter().map(|k| k.trim().to_string()).collect());
+    Settings::update(|s| s.ignored_keys = keys)
+}
@@ -0,0 +1,89 @@
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Mutex};
+
+/// Slicers whose data directory uses OrcaSlicer's `user`/`system`/`filament`
+/// layout, so the same resolution machinery works on all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Slicer {
+    #[default]
+    OrcaSlicer,
+    BambuStudio,
+}
+
+const ALL: [Slicer; 2] = [Slicer::OrcaSlicer, Slicer::BambuStudio];
+
+/// Cached active choice; `None` until first looked up.
+static ACTIVE: Mutex<Option<Slicer>> = Mutex::new(None);
+
+impl Slicer {
+    pub fn label(self) -> &'static str {
+        match self {
+            Slicer::OrcaSlicer => "OrcaSlicer",
+            Slicer::BambuStudio => "Bambu Studio",
+        }
+    }
+
+    fn folder(self) -> &'static str {
+        match self {
+            Slicer::OrcaSlicer => "OrcaSlicer",
+            Slicer::BambuStudio => "BambuStudio",
+        }
+    }
+
+    pub fn root(self) -> PathBuf {
+        dirs_next::home_dir()
+            .unwrap_or_else(|| PathBuf::from("/"))
+            .join("Library/Application Support")
+            .join(self.folder())
+    }
+
+    pub fn is_installed(self) -> bool {
+        self.root().is_dir()
+    }
+}
+
+/// The slicer whose profiles we work on: the stored choice, otherwise the
+/// first one found installed (OrcaSlicer first).
+pub fn active() -> Slicer {
+    let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    *active.get_or_insert_with(|| {
+        Settings::load().slicer.unwrap_or_else(|| {
+            ALL.into_iter()
+                .find(|s| s.is_installed())
+                .unwrap_or_default()
+        })
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlicerInstall {
+    slicer: Slicer,
+    label: &'static str,
+    root: String,
+    installed: bool,
+    active: bool,
+}
+
+#[tauri::command]
+pub fn detect_slicers() -> Vec<SlicerInstall> {
+    let current = active();
+    ALL.into_iter()
+        .map(|slicer| SlicerInstall {
+            slicer,
+            label: slicer.label(),
+            root: slicer.root().display().to_string(),
+            installed: slicer.is_installed(),
+            active: slicer == current,
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_active_slicer(slicer: Slicer) -> Result<(), String> {
+    Settings::update(|s| s.slicer = Some(slicer))?;
+    *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some(slicer);
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::{sanitize_filename, settings::app_config_dir, undo::UndoStack};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::State;
+
+/// A saved copy of one user profile file, kept until pruned.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: String,
+    pub name: String,
+    /// The file the snapshot was taken from, and where restoring writes
+    pub path: String,
+    /// Unix timestamp (milliseconds) of when it was taken
+    pub created: u64,
+    /// The file's contents, byte for byte as it was
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    contents: String,
+}
+
+fn snapshots_dir() -> PathBuf {
+    app_config_dir().join("snapshots")
+}
+
+fn snapshot_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id != sanitize_filename(id) || id.starts_with('.') {
+        return Err(format!("not a snapshot id: '{}'", id));
+    }
+    Ok(snapshots_dir().join(format!("{}.json", id)))
+}
+
+fn load(path: &Path) -> Result<Snapshot, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+    serde_json::from_str(&s).map_err(|e| format!("parse {}: {}", path.display(), e))
+}
+
+/// Every stored snapshot without its contents, newest first
+fn all() -> Vec<Snapshot> {
+    let mut found: Vec<Snapshot> = fs::read_dir(snapshots_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| load(&e.path()).ok())
+        .map(|s| Snapshot {
+            contents: String::new(),
+            ..s
+        })
+        .collect();
+    found.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.id.cmp(&a.id)));
+    found
+}
+
+/// Copies the user profile `name` as it is on disk now into the app data
+/// directory and returns the snapshot's id.
+#[tauri::command]
+pub fn snapshot_profile(name: String) -> Result<String, String> {
+    crate::ensure_writable()?;
+    crate::ensure_installed()?;
+    let file = crate::user_filament_profiles()
+        .remove(&name)
+        .ok_or_else(|| format!("no user profile named '{}'", name))?;
+    let contents =
+        fs::read_to_string(&file).map_err(|e| format!("read {}: {}", file.display(), e))?;
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+
+    let stem = sanitize_filename(&name);
+    let mut id = format!("{}-{}", stem, created);
+    let mut n = 1;
+    while snapshot_path(&id)?.exists() {
+        n += 1;
+        id = format!("{}-{}-{}", stem, created, n);
+    }
+
+    let snapshot = Snapshot {
+        id: id.clone(),
+        name,
+        path: file.display().to_string(),
+        created,
+        contents,
+    };
+    let dir = snapshots_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    let path = snapshot_path(&id)?;
+    let s = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    fs::write(&path, s).map_err(|e| format!("write {}: {}", path.display(), e))?;
+    Ok(id)
+}
+
+/// Puts the snapshot's contents back where it was taken from, recreating
+/// the file if it was deleted since. The overwrite can be undone; the
+/// snapshot itself is kept.
+#[tauri::command]
+pub fn restore_snapshot(id: String, undo: State<'_, UndoStack>) -> Result<String, String> {
+    crate::ensure_writable()?;
+    let snapshot = load(&snapshot_path(&id)?)?;
+    let target = PathBuf::from(&snapshot.path);
+    undo.record_while(&format!("restore {}", snapshot.name), &[&target], || {
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        }
+        fs::write(&target, snapshot.contents.as_bytes())
+            .map_err(|e| format!("write {}: {}", target.display(), e))
+    })?;
+    Ok(snapshot.path)
+}
+
+/// Stored snapshots, newest first, optionally only those of profile `name`.
+#[tauri::command]
+pub fn list_snapshots(name: Option<String>) -> Result<Vec<Snapshot>, String> {
+    Ok(all()
+        .into_iter()
+        .filter(|s| name.as_ref().is_none_or(|n| &s.name == n))
+        .collect())
+}
+
+/// Deletes all but the newest `keep` snapshots of each profile, or of
+/// `name` alone when given, and returns the ids removed.
+#[tauri::command]
+pub fn prune_snapshots(name: Option<String>, keep: usize) -> Result<Vec<String>, String> {
+    crate::ensure_writable()?;
+    let mut seen = std::collections::HashMap::<String, usize>::new();
+    let mut removed = Vec::new();
+    for s in list_snapshots(name)? {
+        let count = seen.entry(s.name).or_default();
+        *count += 1;
+        if *count <= keep {
+            continue;
+        }
+        let path = snapshot_path(&s.id)?;
+        fs::remove_file(&path).map_err(|e| format!("remove {}: {}", path.display(), e))?;
+        removed.push(s.id);
+    }
+    Ok(removed)
+}
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::cell::Cell;
+
+/// Filesystem work done on the current thread, for performance diagnostics.
+///
+/// The counters only ever grow; take a [`snapshot`] before and after the
+/// work to measure and subtract with [`IoStats::since`]. They are kept per
+/// thread so concurrent commands don't count each other's reads.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IoStats {
+    /// Profile files opened, retries included
+    pub file_reads: u64,
+    /// Directories listed
+    pub dir_scans: u64,
+}
+
+thread_local! {
+    static COUNTERS: Cell<IoStats> = const {
+        Cell::new(IoStats {
+            file_reads: 0,
+            dir_scans: 0,
+        })
+    };
+}
+
+impl IoStats {
+    /// The work done between `earlier` and `self`
+    pub fn since(self, earlier: IoStats) -> IoStats {
+        IoStats {
+            file_reads: self.file_reads - earlier.file_reads,
+            dir_scans: self.dir_scans - earlier.dir_scans,
+        }
+    }
+}
+
+pub fn snapshot() -> IoStats {
+    COUNTERS.with(Cell::get)
+}
+
+fn bump(f: impl FnOnce(&mut IoStats)) {
+    COUNTERS.with(|c| {
+        let mut s = c.get();
+        f(&mut s);
+        c.set(s);
+    });
+}
+
+pub fn count_file_read() {
+    bump(|s| s.file_reads += 1);
+}
+
+pub fn count_dir_scan() {
+    bump(|s| s.dir_scans += 1);
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
+
+/// How a name in the chain was turned into a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceMethod {
+    /// The caller named the file, e.g. a user profile picked from the list
+    Leaf,
+    /// A wildcard `inherits` was expanded to one profile name
+    Wildcard,
+    /// `pins` named the file
+    Pin,
+    /// A path in `inherits`, relative to the child's folder
+    Relative,
+    /// The user and system folders were searched by file name
+    Search,
+    /// Nothing had the name, so its alias was searched for instead
+    Alias,
+}
+
+/// One decision taken while resolving a chain.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    pub looked_for: String,
+    pub method: TraceMethod,
+    /// The file (or name, for wildcards and aliases) it settled on; `None`
+    /// when this step found nothing and the next one is the fallback
+    pub found: Option<String>,
+    /// Every file that answered, in order of preference, when there was a
+    /// choice to make
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+}
+
+thread_local! {
+    /// `Some` while [`capture`] runs on this thread
+    static STEPS: RefCell<Option<Vec<TraceStep>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, collecting every step recorded on this thread meanwhile.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<TraceStep>) {
+    let outer = STEPS.with(|s| s.replace(Some(Vec::new())));
+    let out = f();
+    let steps = STEPS.with(|s| s.replace(outer)).unwrap_or_default();
+    (out, steps)
+}
+
+/// Notes a step if a [`capture`] is running; free otherwise.
+pub fn record(looked_for: &str, method: TraceMethod, found: Option<&Path>, candidates: &[PathBuf]) {
+    record_step(|| TraceStep {
+        looked_for: looked_for.to_string(),
+        method,
+        found: found.map(|p| p.display().to_string()),
+        candidates: candidates.iter().map(|p| p.display().to_string()).collect(),
+    });
+}
+
+/// [`record`] for steps that settle on a name rather than a file
+pub fn record_name(looked_for: &str, method: TraceMethod, found: Option<&str>) {
+    record_step(|| TraceStep {
+        looked_for: looked_for.to_string(),
+        method,
+        found: found.map(String::from),
+        candidates: Vec::new(),
+    });
+}
+
+fn record_step(step: impl FnOnce() -> TraceStep) {
+    STEPS.with(|s| {
+        if let Some(steps) = s.borrow_mut().as_mut() {
+            steps.push(step());
+        }
+    });
+}
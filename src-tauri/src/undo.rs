@@ -0,0 +1,145 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// How many operations are kept before the oldest snapshot is discarded.
+const MAX_OPERATIONS: usize = 50;
+
+/// In-session undo for commands that overwrite or remove files.
+///
+/// A destructive command touches disk inside [`UndoStack::record_while`],
+/// naming every path it is about to change; the current contents are copied
+/// into a temp directory keyed by operation id so they can be put back.
+pub struct UndoStack {
+    dir: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    ops: Vec<Operation>,
+}
+
+struct Operation {
+    id: u64,
+    label: String,
+    files: Vec<Snapshot>,
+}
+
+struct Snapshot {
+    original: PathBuf,
+    /// `None` when the file did not exist yet; undoing removes it again.
+    saved: Option<PathBuf>,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        let dir = std::env::temp_dir().join(format!("orca-exporter-undo-{}", std::process::id()));
+        UndoStack {
+            dir,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+}
+
+impl UndoStack {
+    /// Snapshots `paths` as they are right now under a new operation, then
+    /// runs `write`, which changes them. When `write` fails, whatever it
+    /// already changed is put back and the operation is dropped, so a failed
+    /// command never leaves an entry on top that would undo nothing.
+    pub fn record_while<T>(
+        &self,
+        label: &str,
+        paths: &[&Path],
+        write: impl FnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        let id = self.push(label, paths)?;
+        write().inspect_err(|_| {
+            let _ = self.undo(|ops| ops.iter().position(|op| op.id == id));
+        })
+    }
+
+    /// Takes the snapshots for [`record_while`](Self::record_while) and
+    /// returns the new operation's id
+    fn push(&self, label: &str, paths: &[&Path]) -> Result<u64, String> {
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        inner.next_id += 1;
+        let id = inner.next_id;
+        let op_dir = self.dir.join(id.to_string());
+        let mut files = Vec::new();
+        for (i, original) in paths.iter().enumerate() {
+            let saved = if original.is_file() {
+                fs::create_dir_all(&op_dir)
+                    .map_err(|e| format!("create {}: {}", op_dir.display(), e))?;
+                let dst = op_dir.join(i.to_string());
+                fs::copy(original, &dst)
+                    .map_err(|e| format!("snapshot {}: {}", original.display(), e))?;
+                Some(dst)
+            } else {
+                None
+            };
+            files.push(Snapshot {
+                original: original.to_path_buf(),
+                saved,
+            });
+        }
+
+        inner.ops.push(Operation {
+            id,
+            label: label.to_string(),
+            files,
+        });
+        if inner.ops.len() > MAX_OPERATIONS {
+            let dropped = inner.ops.remove(0);
+            let _ = fs::remove_dir_all(self.dir.join(dropped.id.to_string()));
+        }
+        Ok(id)
+    }
+
+    /// Restores the files of the most recent operation and returns its label.
+    /// The operation goes back on the stack if a file can't be restored, so a
+    /// failed undo can be retried; its snapshots are deleted once it succeeds.
+    pub fn undo_last(&self) -> Result<String, String> {
+        self.undo(|ops| ops.len().checked_sub(1))
+    }
+
+    /// Restores and removes the operation `pick` finds; see [`undo_last`](Self::undo_last).
+    fn undo(&self, pick: impl FnOnce(&[Operation]) -> Option<usize>) -> Result<String, String> {
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        let i = pick(&inner.ops).ok_or_else(|| "nothing to undo".to_string())?;
+        let op = inner.ops.remove(i);
+        if let Err(e) = op.restore() {
+            inner.ops.insert(i, op);
+            return Err(e);
+        }
+        let _ = fs::remove_dir_all(self.dir.join(op.id.to_string()));
+        Ok(op.label)
+    }
+}
+
+impl Operation {
+    /// Puts every snapshot back where it was taken from
+    fn restore(&self) -> Result<(), String> {
+        for snap in &self.files {
+            match &snap.saved {
+                Some(saved) => {
+                    if let Some(parent) = snap.original.parent() {
+                        fs::create_dir_all(parent)
+                            .map_err(|e| format!("create {}: {}", parent.display(), e))?;
+                    }
+                    fs::copy(saved, &snap.original)
+                        .map_err(|e| format!("restore {}: {}", snap.original.display(), e))?;
+                }
+                None if snap.original.is_file() => {
+                    fs::remove_file(&snap.original)
+                        .map_err(|e| format!("remove {}: {}", snap.original.display(), e))?;
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
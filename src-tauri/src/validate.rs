@@ -0,0 +1,128 @@
+use crate::normalize;
+use serde_json::{Map, Value};
+
+/// Keys a resolved filament profile needs before OrcaSlicer can slice with it.
+pub const REQUIRED_KEYS: &[&str] = &[
+    "name",
+    "type",
+    "filament_type",
+    "filament_diameter",
+    "filament_flow_ratio",
+    "filament_max_volumetric_speed",
+    "nozzle_temperature",
+    "nozzle_temperature_initial_layer",
+];
+
+/// Settings a print can't safely go without: nozzle and bed temperatures and
+/// the flow ratio. Each must hold a number for every extruder.
+pub const ESSENTIAL_KEYS: &[&str] = &[
+    "nozzle_temperature",
+    "nozzle_temperature_initial_layer",
+    "hot_plate_temp",
+    "hot_plate_temp_initial_layer",
+    "filament_flow_ratio",
+];
+
+/// Keys OrcaSlicer stores as a single string rather than a list.
+pub const STRING_KEYS: &[&str] = &[
+    "name",
+    "inherits",
+    "from",
+    "type",
+    "instantiation",
+    "setting_id",
+    "filament_id",
+    "version",
+    "compatible_printers_condition",
+    "compatible_prints_condition",
+];
+
+/// Whether `v` holds nothing: null, `""`, or a list of only those
+pub fn is_empty(v: &Value) -> bool {
+    match v {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty() || a.iter().all(is_empty),
+        _ => false,
+    }
+}
+
+/// Required keys that are present but hold nothing, e.g. `""` or `[""]`
+pub fn empty_required_keys(profile: &Map<String, Value>) -> Vec<&'static str> {
+    REQUIRED_KEYS
+        .iter()
+        .copied()
+        .filter(|k| profile.get(*k).is_some_and(is_empty))
+        .collect()
+}
+
+/// Essential keys that are absent, empty or don't hold a number.
+pub fn missing_essential_keys(profile: &Map<String, Value>) -> Vec<&'static str> {
+    ESSENTIAL_KEYS
+        .iter()
+        .copied()
+        .filter(|k| match profile.get(*k) {
+            Some(Value::Array(items)) => {
+                items.is_empty() || items.iter().any(|i| normalize::as_number(i).is_none())
+            }
+            Some(v) => normalize::as_number(v).is_none(),
+            None => true,
+        })
+        .collect()
+}
+
+/// Checks a resolved profile against the shape OrcaSlicer writes: required
+/// keys present and non-empty, metadata keys as strings, everything else as
+/// strings or lists of strings, and boolean keys spelled as booleans.
+pub fn validate_profile(profile: &Map<String, Value>) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    for key in REQUIRED_KEYS {
+        match profile.get(*key) {
+            None => issues.push(format!("missing required key '{}'", key)),
+            Some(v) if is_empty(v) => issues.push(format!("required key '{}' is empty", key)),
+            Some(_) => {}
+        }
+    }
+
+    for (key, v) in profile {
+        let ok = if STRING_KEYS.contains(&key.as_str()) {
+            v.is_string()
+        } else {
+            match v {
+                Value::String(_) => true,
+                Value::Array(items) => items.iter().all(Value::is_string),
+                _ => false,
+            }
+        };
+        if !ok {
+            let expected = if STRING_KEYS.contains(&key.as_str()) {
+                "a string"
+            } else {
+                "a string or a list of strings"
+            };
+            issues.push(format!("'{}' should be {}", key, expected));
+        }
+    }
+
+    for (key, v) in profile {
+        if !normalize::is_bool_key(key) {
+            continue;
+        }
+        let items = match v {
+            Value::Array(items) => items.as_slice(),
+            v => std::slice::from_ref(v),
+        };
+        if items.iter().any(|i| normalize::as_bool(i).is_none()) {
+            issues.push(format!("'{}' should hold booleans", key));
+        }
+    }
+
+    if let Some(kind) = profile.get("type").and_then(Value::as_str) {
+        if kind != "filament" {
+            issues.push(format!("type is '{}', expected 'filament'", kind));
+        }
+    }
+
+    issues
+}